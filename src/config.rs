@@ -1,19 +1,153 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use serde::Deserialize;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 pub struct RustdocmdConfig {
     pub paths: Paths,
+    #[serde(default)]
+    pub summary: SummaryConfig,
+    #[serde(default)]
+    pub inject: InjectConfig,
+    #[serde(default)]
+    pub readme: ReadmeConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Paths {
     pub source: String,
-    pub target: String,
+    /// Ordner für Kapitel und SUMMARY.md; entfällt, wenn `book` gesetzt ist
+    #[serde(default)]
+    pub target: Option<String>,
+    /// book.toml des mdBooks; Kapitelordner und Titel kommen dann von dort
+    #[serde(default)]
+    pub book: Option<String>,
+}
+
+/// Wo Kapitel und SUMMARY.md landen
+#[derive(Debug, Clone)]
+pub struct BookLayout {
+    /// Buch-Root mit book.toml (ohne `paths.book`: Elternordner von `paths.target`)
+    pub root: PathBuf,
+    /// Ordner mit SUMMARY.md und den Kapiteln (bei mdBook `[book] src`)
+    pub target_dir: PathBuf,
+    /// Titel des Buchs für eine neu angelegte SUMMARY.md
+    pub title: Option<String>,
+    /// Zusätzliche Kopie der SUMMARY.md (nur ohne book.toml, `--mirror-root-summary`)
+    pub mirror_summary: Option<PathBuf>,
+}
+
+impl BookLayout {
+    pub fn summary_path(&self) -> PathBuf {
+        self.target_dir.join("SUMMARY.md")
+    }
+}
+
+/// Einstellungen für SUMMARY.md (`[summary]`)
+#[derive(Debug, Default, Deserialize)]
+pub struct SummaryConfig {
+    #[serde(default)]
+    pub duplicate_order: DuplicateOrder,
+    /// Titel aus dem Dateinamen, wenn weder `title=` noch eine Überschrift vorhanden ist
+    #[serde(default)]
+    pub title_transform: TitleTransform,
+    /// Wörter, die im Titel so geschrieben werden wie hier angegeben (z.B. "API")
+    #[serde(default)]
+    pub acronyms: Vec<String>,
+}
+
+/// Dateien, in deren Bereiche `<inject file="...">`-Blöcke eingesetzt werden (`[inject]`)
+#[derive(Debug, Default, Deserialize)]
+pub struct InjectConfig {
+    /// Pfade relativ zum Arbeitsverzeichnis, z.B. "CONTRIBUTING.md" oder "docs/ops.md"
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/// Aufbau der Bereiche in README.md (`[readme]`)
+#[derive(Debug, Deserialize)]
+pub struct ReadmeConfig {
+    /// Trenner zwischen Blöcken und Abschnitten
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    /// Inhaltsverzeichnis der Abschnitte im Bereich `<!-- rustdocmd:begin toc -->`
+    #[serde(default)]
+    pub toc: bool,
+}
+
+fn default_separator() -> String {
+    "\n\n".to_string()
+}
+
+impl Default for ReadmeConfig {
+    fn default() -> Self {
+        ReadmeConfig {
+            separator: default_separator(),
+            toc: false,
+        }
+    }
+}
+
+/// Umwandlung des Dateinamens in einen Titel, z.B. für `getting-started.md`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleTransform {
+    /// "Getting Started"
+    #[default]
+    TitleCase,
+    /// "Getting started"
+    SentenceCase,
+    /// "getting-started"
+    FileName,
+}
+
+/// Umgang mit Kapiteln, die dieselbe Ordnungsnummer tragen. Sie werden immer
+/// nach Quelldatei und Zeile sortiert; die Variante legt fest, ob das ein Fehler,
+/// eine Warnung oder gewollt ist.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateOrder {
+    Error,
+    #[default]
+    Warn,
+    Path,
 }
 
 impl RustdocmdConfig {
+    /// Ermittelt Kapitelordner und Titel: aus der book.toml (`paths.book`) wie
+    /// mdBook selbst, sonst aus `paths.target` mit der SUMMARY.md-Kopie im
+    /// übergeordneten Ordner
+    pub fn layout(&self, mirror_root_summary: bool) -> Result<BookLayout> {
+        if let Some(book) = &self.paths.book {
+            if self.paths.target.is_some() {
+                eprintln!("Warnung: paths.target wird ignoriert, weil paths.book gesetzt ist");
+            }
+            let book_toml = Path::new(book);
+            let book_config = mdbook::Config::from_disk(book_toml)
+                .map_err(|e| anyhow!("{} nicht lesbar: {:#}", book_toml.display(), e))?;
+            let root = book_toml.parent().unwrap_or(Path::new(""));
+            return Ok(BookLayout {
+                root: root.to_path_buf(),
+                target_dir: root.join(&book_config.book.src),
+                title: book_config.book.title,
+                mirror_summary: None,
+            });
+        }
+        let Some(target) = &self.paths.target else {
+            bail!("rustdocmd.toml: paths.target oder paths.book fehlt");
+        };
+        let target_dir = PathBuf::from(target);
+        let root = target_dir.parent().unwrap_or(Path::new("")).to_path_buf();
+        let mirror_summary = mirror_root_summary.then(|| root.join("SUMMARY.md"));
+        Ok(BookLayout {
+            root,
+            target_dir,
+            title: None,
+            mirror_summary,
+        })
+    }
+
     pub fn from_file(path: &str) -> Result<Self> {
         let content = fs::read_to_string(path)?;
         let config: RustdocmdConfig = toml::from_str(&content)?;
@@ -1,6 +1,6 @@
 mod config;
 /// <introducing.md(1)> "main.rs"
-/// <readme>
+/// <readme name="intro">
 /// rustdocmd
 ///
 /// With this tool, you can write comprehensive, versioned documentation directly in your Rust code using rustdoc comments (`///` or `//!`).
@@ -32,23 +32,22 @@ mod config;
 ///    ```toml
 ///    [paths]
 ///    source = "./src"
-///    target = "./mdbook/src"
+///    book = "./mdbook/book.toml"
 ///    ```
+///    rustdocmd reads `[book] src` and `title` from `book.toml` and writes the chapters and `SUMMARY.md` exactly where mdBook expects them.
 ///
-/// 4. (Optional) Install mdBook if not already installed:
-///    ```sh
-///    cargo install mdbook
-///    ```
-/// 5. Run the tool:
+/// 4. Run the tool:
 ///    ```sh
 ///    ./target/release/rustdocmd
 ///    ```
-/// 6. View the documentation locally:
+///    `rustdocmd build` additionally builds the book (like `mdbook build`, no separate mdBook installation needed).
+/// 5. View the documentation locally:
 ///    ```sh
-///    cd mdbook
-///    mdbook serve
+///    ./target/release/rustdocmd serve
 ///    # open http://localhost:3000 in your browser
 ///    ```
+///    `serve` rebuilds the book and reloads the page whenever a `.rs` file below `paths.source` or a chapter in the book changes. Use `--port` and `--hostname` to listen elsewhere. Without a book server, `rustdocmd watch` keeps the Markdown files up to date: after the first full run it re-parses only the `.rs` files that changed and rewrites only the chapters whose blocks changed. Blocks moved to another file end up in their chapter as before, and chapters whose blocks are gone are removed.
+///
 /// </install.md>
 /// <example.md(3)> "main.rs"
 ///
@@ -65,48 +64,125 @@ mod config;
 ///
 /// After running `rustdocmd`, this will automatically generate a Markdown file `chapter.md` and include it in your mdBook.
 /// The order in the table of contents is controlled by the number in parentheses `(1)`.
+/// Dotted numbers such as `(2.1)` or `(2.1.3)` create sub-chapters, which are nested below their parent chapter in `SUMMARY.md`. Missing parents are reported as warnings.
+///
+/// Chapters sharing the same number are never dropped: they are sorted by source file and line. Whether that is reported is set in `rustdocmd.toml`:
+///
+/// ```toml
+/// [summary]
+/// duplicate_order = "warn" # "error" aborts, "path" sorts silently
+/// title_transform = "title-case" # titles from file names: "sentence-case", "file-name"
+/// acronyms = ["API", "CLI"]       # kept as written in titles from file names
+/// ```
+///
+/// Instead of (or in addition to) a number, a chapter can be placed relative to another one: `<faq.md after="intro.md">` or `<changelog.md before="faq.md">`. Chapters without a number are placed right next to the referenced chapter. Cycles and references to unknown chapters abort the run with an error.
+///
+/// Opening tags accept further attributes: `title="Getting Started"` sets the title in the table of contents (otherwise the first heading of the chapter is used, and as a last resort the file name), `draft` lists the chapter as an mdBook draft without writing a file, and `parent`, `audience` and `weight` are recorded as metadata. Unknown attributes produce a warning.
 ///
 /// You can use as many such marker blocks as you like to structure your documentation.
 ///
-/// If a block in the Rust code is changed, simply update the documentation by running `rustdocmd` again.
+/// Targets may contain subfolders, e.g. `<guide/setup.md(2)>`; the folders are created below the target directory. Targets that would leave the target directory (absolute paths or `..`) are rejected.
+///
+/// Several blocks (even from different files) may use the same target; they are merged into one chapter with a single entry in the table of contents. Use a fragment index such as `<architecture.md(3)#2>` to control their order; fragments without an index follow, sorted by file path.
+///
+/// If a block in the Rust code is changed, simply update the documentation by running `rustdocmd` again. Only files whose content actually changes are rewritten, so modification times stay stable for `mdbook serve` and incremental builds; the run reports how many files were updated. All outputs of a run (chapters, `SUMMARY.md`, `README.md`, injected files and deletions) are first written to temporary files and only then renamed into place; if anything fails, the previous state is restored.
+///
+/// If a block is removed, the corresponding Markdown file and the entry in the table of contents will be automatically deleted. rustdocmd records the files it generated in `.rustdocmd-manifest` in the book root (next to `book.toml`, outside the `src` folder so mdBook does not copy it into the book) and only ever deletes files listed there; hand-written Markdown files next to the generated chapters are kept (with a warning if `SUMMARY.md` does not link them).
 ///
-/// If a block is removed, the corresponding Markdown file and the entry in the table of contents will be automatically deleted.
+/// rustdocmd only maintains the entries between `<!-- rustdocmd:begin -->` and `<!-- rustdocmd:end -->` in `SUMMARY.md`. Everything outside this region (prefix chapters, part titles, separators, external links) is kept as written. When the region is missing, the entries pointing to generated chapters are replaced by it.
 ///
 /// ## Generate README.md
-/// To include a section in your `README.md`, use a marker like this:
+/// To include a section in your `README.md`, use a named marker like this:
 ///
 /// ```rust
-/// /// <readme>
+/// /// <readme name="usage">
 /// /// # My Section
 /// /// This text will appear in the README.
 /// /// </readme>
 /// ```
 ///
-/// When you run `rustdocmd --generate-readme`, all such blocks are collected and written to `README.md` (previous content will be overwritten).
+/// and mark the place in `README.md` where it belongs:
+///
+/// ```markdown
+/// <!-- rustdocmd:begin usage -->
+/// <!-- rustdocmd:end usage -->
+/// ```
+///
+/// When you run `rustdocmd --generate-readme`, each region is replaced by the blocks with the same name (in source order); blocks without a name fill `<!-- rustdocmd:begin -->` ... `<!-- rustdocmd:end -->`. Everything outside the regions (badges, license, contribution notes) is kept byte for byte. A `README.md` without any regions is left unchanged; a missing one is created with one region per name.
 /// Without the `--generate-readme` flag, your `README.md` remains unchanged.
 ///
-/// ## Mirroring SUMMARY.md (mdBook)
+/// The same works for any other Markdown file, e.g. `CONTRIBUTING.md` or `docs/ops.md`: list it in `rustdocmd.toml` and use an `<inject>` block (`<readme name="x">` is short for `<inject file="README.md" region="x">`):
 ///
-/// By default, rustdocmd writes and maintains the table of contents for your mdBook at `mdbook/src/SUMMARY.md` (the location expected by mdBook).
-/// For greater compatibility, this file can also be mirrored to `mdbook/SUMMARY.md` (project root), so both versions are always in sync.
+/// ```toml
+/// [inject]
+/// files = ["CONTRIBUTING.md", "docs/ops.md"]
+/// ```
 ///
-/// - Default: Mirroring is enabled.
-/// - You can disable it with the following CLI flag:
+/// ```rust
+/// /// <inject file="CONTRIBUTING.md" region="build">
+/// /// Run `cargo build --release`.
+/// /// </inject>
+/// ```
+///
+/// Blocks for the same region are assembled in a fixed order: first by an order number such as `<readme(2)>`, then by source file and line. A `section="Usage"` attribute groups blocks under a `## Usage` heading. The separator between blocks and an optional table of contents of these sections (written to the region `<!-- rustdocmd:begin toc -->`) are set in `rustdocmd.toml`:
+///
+/// ```toml
+/// [readme]
+/// separator = "\n\n---\n\n" # default: an empty line
+/// toc = true
+/// ```
+///
+/// These files are updated on every run, but only if they exist; blocks for files that are not listed are ignored with a warning.
+///
+/// ## Checking generated files in CI
+///
+/// `rustdocmd --check` runs the full extraction in memory and compares the result with the files on disk. It lists every file that is stale, missing or would be deleted and exits with a non-zero status without writing anything; if everything is up to date it exits with 0. `--dry-run` shows the planned writes and deletions instead.
+///
+/// Both modes print a unified diff against the current file contents for every chapter, `SUMMARY.md` and `README.md` that would change (coloured when writing to a terminal, plain with `NO_COLOR` set), followed by a summary such as `1 neu, 2 geändert, 5 unverändert, 0 gelöscht`.
+///
+/// ## Using rustdocmd as an mdBook preprocessor
+///
+/// Instead of generating `.md` files, rustdocmd can inject the chapters directly into the book while `mdbook build` runs. Register it in `book.toml`:
+///
+/// ```toml
+/// [preprocessor.rustdocmd]
+/// command = "rustdocmd preprocess"
+/// source = "../src"               # relative to the book root (default)
+/// summary = { title_transform = "sentence-case" } # same options as [summary]
+/// ```
+///
+/// Chapters already listed in `SUMMARY.md` get their content from the source code; all other chapters are appended to the book, sub-chapters below their parent. Nothing has to be committed or kept in sync.
+///
+/// ## Without book.toml: target folder and mirrored SUMMARY.md
+///
+/// Instead of `paths.book`, the chapter folder can be given directly as `paths.target = "./mdbook/src"`. rustdocmd then cannot know where the book root is and by default also mirrors `SUMMARY.md` to the parent folder (`mdbook/SUMMARY.md`). Disable this with:
 ///
 /// ```
 /// ./target/release/rustdocmd --mirror-root-summary=false
 /// ```
 ///
-/// If mirroring is disabled, only `mdbook/src/SUMMARY.md` will be updated; the file in the project root will remain untouched.
+/// With `paths.book`, no mirror is written.
 /// </example.md>
+mod output;
 mod parser;
+mod preprocessor;
+mod region;
+mod serve;
+mod summary;
+mod watch;
 mod writer;
 
-use anyhow::Result;
-use clap::Parser;
-use config::RustdocmdConfig;
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand};
+use config::{BookLayout, ReadmeConfig, RustdocmdConfig};
+use mdbook::preprocess::Preprocessor;
+use output::OutputPlan;
+use parser::{InjectBlock, MarkerBlock, README_FILE};
+use preprocessor::RustdocmdPreprocessor;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 #[derive(Parser)]
@@ -117,92 +193,305 @@ struct Cli {
     /// Nur anzeigen, was geschrieben/entfernt würde (keine Änderungen)
     #[arg(long, default_value_t = false)]
     dry_run: bool,
-    /// Zusätzlich die SUMMARY.md im mdBook-Root spiegeln (mdbook/SUMMARY.md)
+    /// Prüfen, ob die erzeugten Dateien aktuell sind; Exit-Code ungleich 0, falls nicht (für CI)
+    #[arg(long, default_value_t = false, conflicts_with = "dry_run")]
+    check: bool,
+    /// Zusätzlich die SUMMARY.md im mdBook-Root spiegeln (mdbook/SUMMARY.md, nur mit paths.target)
     #[arg(long, action = clap::ArgAction::Set, default_value_t = true)]
     mirror_root_summary: bool,
-    /// README.md aus <readme>-Blöcken generieren (überschreibt README.md!)
+    /// <readme>-Blöcke in die rustdocmd-Bereiche der README.md einsetzen
     #[arg(long, default_value_t = false)]
     generate_readme: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Als mdBook-Präprozessor laufen (book.toml: command = "rustdocmd preprocess")
+    Preprocess {
+        #[command(subcommand)]
+        supports: Option<PreprocessCommand>,
+    },
+    /// Dokumentation erzeugen und das mdBook bauen
+    Build,
+    /// Dokumentation erzeugen, das mdBook bauen und mit Live-Reload ausliefern;
+    /// Änderungen an .rs-Dateien lösen eine neue Extraktion aus
+    Serve {
+        #[arg(short = 'n', long, default_value = "localhost")]
+        hostname: String,
+        #[arg(short, long, default_value_t = 3000)]
+        port: u16,
+    },
+    /// Dokumentation erzeugen und bei Änderungen an .rs-Dateien aktualisieren;
+    /// neu geparst werden nur die geänderten Dateien
+    Watch,
+}
+
+#[derive(Subcommand)]
+enum PreprocessCommand {
+    /// Von mdBook aufgerufen: unterstützt der Präprozessor diesen Renderer?
+    Supports { renderer: String },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if let Some(Command::Preprocess { supports }) = &cli.command {
+        return match supports {
+            Some(PreprocessCommand::Supports { renderer }) => {
+                if RustdocmdPreprocessor.supports_renderer(renderer) {
+                    Ok(())
+                } else {
+                    std::process::exit(1)
+                }
+            }
+            None => preprocessor::handle_preprocessing(),
+        };
+    }
     let config = RustdocmdConfig::from_file(&cli.config)?;
-    let source_dir = &config.paths.source;
-    let target_dir = &config.paths.target;
-    let summary_path = Path::new(target_dir).join("SUMMARY.md");
-
-    if !cli.dry_run {
-        fs::create_dir_all(target_dir)?;
+    let layout = config.layout(cli.mirror_root_summary)?;
+    match &cli.command {
+        Some(Command::Build) => {
+            generate(&cli, &config, &layout)?;
+            build_book(&layout)
+        }
+        Some(Command::Serve { hostname, port }) => {
+            generate(&cli, &config, &layout)?;
+            build_book(&layout)?;
+            serve::serve(
+                &layout,
+                Path::new(&config.paths.source),
+                hostname,
+                *port,
+                || generate(&cli, &config, &layout),
+            )
+        }
+        Some(Command::Watch) => watch_sources(&cli, &config, &layout),
+        _ => generate(&cli, &config, &layout),
     }
+}
 
-    let mut all_blocks = Vec::new();
-    let mut parsed_files = Vec::new();
-    let mut all_readme_blocks = Vec::new();
-    for entry in WalkDir::new(source_dir).into_iter().filter_map(|e| e.ok()) {
-        if entry
-            .path()
-            .extension()
-            .map(|ext| ext == "rs")
-            .unwrap_or(false)
-        {
-            let content = fs::read_to_string(entry.path())?;
-            let doc_comments = parser::extract_rustdoc_comments(&content);
+/// Liest alle Marker- und Inject-Blöcke einer Quelldatei und gibt sie aus.
+/// Eine nicht parsebare Datei bricht den Lauf ab, damit ihre Kapitel nicht als
+/// verschwunden gelten und gelöscht werden.
+fn extract_file(path: &Path) -> Result<(Vec<MarkerBlock>, Vec<InjectBlock>)> {
+    let content = fs::read_to_string(path)?;
+    let docs = parser::extract_rustdoc_comments(&content, path)?;
+    println!("\n[Rustdoc-Kommentare aus {}]:", path.display());
+    for doc in &docs {
+        let line = doc.lines.first().map_or(0, |l| l.line);
+        println!("({}, Zeile {})\n{}", doc.item, line, doc.texts().join("\n"));
+    }
+    let blocks = parser::extract_marker_blocks(&docs);
+    let inject_blocks = parser::extract_inject_blocks(&docs);
+    if !blocks.is_empty() {
+        println!("Marker gefunden in: {}", path.display());
+        for (i, block) in blocks.iter().enumerate() {
             println!(
-                "\n[Rustdoc-Kommentare aus {}]:\n{}",
-                entry.path().display(),
-                doc_comments
+                "  Block {} ({}, {}): target_md={}, order={:?}, attributes={:?}, source_ref='{}', content='{}'",
+                i + 1,
+                block.item,
+                block.location,
+                block.target_md,
+                block.order,
+                block.attributes,
+                block.source_ref,
+                block.content
             );
-            let blocks = parser::extract_marker_blocks(&content);
-            let readme_blocks = parser::extract_readme_blocks(&content);
-            if !blocks.is_empty() {
-                println!("Marker gefunden in: {}", entry.path().display());
-                for (i, block) in blocks.iter().enumerate() {
-                    println!(
-                        "  Block {}: target_md={}, order={:?}, source_ref='{}', content='{}'",
-                        i + 1,
-                        block.target_md,
-                        block.order,
-                        block.source_ref,
-                        block.content
-                    );
+        }
+    }
+    for block in &inject_blocks {
+        println!(
+            "  Inject-Block ({}, {}): file={}, region='{}', {} bytes",
+            block.item,
+            block.location,
+            block.file,
+            block.region,
+            block.content.len()
+        );
+    }
+    Ok((blocks, inject_blocks))
+}
+
+/// Marker- und Inject-Blöcke je Quelldatei, nach Pfad sortiert
+type SourceBlocks = BTreeMap<PathBuf, (Vec<MarkerBlock>, Vec<InjectBlock>)>;
+
+fn is_rust_file(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "rs").unwrap_or(false)
+}
+
+/// Liest alle .rs-Dateien unter `dir` ein
+fn scan_sources(dir: &Path, sources: &mut SourceBlocks) -> Result<()> {
+    for entry in WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_file() && is_rust_file(entry.path()) {
+            let blocks = extract_file(entry.path())?;
+            sources.insert(entry.path().to_path_buf(), blocks);
+        }
+    }
+    Ok(())
+}
+
+/// Liest nur die geänderten Pfade neu ein. Entfernte Dateien und Ordner verlieren
+/// ihre Blöcke, hinzugekommene Ordner werden durchsucht; alle anderen Dateien
+/// behalten ihren Stand. Lässt sich eine Datei nicht parsen (z.B. halb getippt),
+/// bleiben ihre bisherigen Blöcke erhalten.
+fn update_sources(sources: &mut SourceBlocks, changed: &BTreeSet<PathBuf>) {
+    for path in changed {
+        if path.is_file() {
+            if !is_rust_file(path) {
+                continue;
+            }
+            match extract_file(path) {
+                Ok(blocks) => {
+                    sources.insert(path.clone(), blocks);
                 }
+                Err(e) => eprintln!("Fehler: {:#} (bisheriger Stand bleibt erhalten)", e),
             }
-            all_blocks.extend(blocks);
-            all_readme_blocks.extend(readme_blocks);
-            parsed_files.push(entry.path().display().to_string());
+            continue;
         }
+        let mut scanned = SourceBlocks::new();
+        if path.is_dir() {
+            if let Err(e) = scan_sources(path, &mut scanned) {
+                eprintln!("Fehler: {:#} (bisheriger Stand bleibt erhalten)", e);
+                continue;
+            }
+        }
+        sources.retain(|file, _| !file.starts_with(path));
+        sources.extend(scanned);
     }
+}
+
+/// Extrahiert alle Blöcke aus `paths.source` und schreibt (bzw. prüft) Kapitel,
+/// SUMMARY.md und die Inject-Dateien
+fn generate(cli: &Cli, config: &RustdocmdConfig, layout: &BookLayout) -> Result<()> {
+    let mut sources = SourceBlocks::new();
+    scan_sources(Path::new(&config.paths.source), &mut sources)?;
+    write_sources(cli, config, layout, &sources)
+}
+
+/// Schreibt die Ausgaben zu den Blöcken aller Quelldateien
+fn write_sources(
+    cli: &Cli,
+    config: &RustdocmdConfig,
+    layout: &BookLayout,
+    sources: &SourceBlocks,
+) -> Result<()> {
     println!("Geparste Dateien:");
-    for file in &parsed_files {
-        println!("- {}", file);
+    for file in sources.keys() {
+        println!("- {}", file.display());
     }
+    let all_blocks: Vec<MarkerBlock> = sources
+        .values()
+        .flat_map(|(blocks, _)| blocks.iter().cloned())
+        .collect();
+    let all_inject_blocks: Vec<InjectBlock> = sources
+        .values()
+        .flat_map(|(_, inject_blocks)| inject_blocks.iter().cloned())
+        .collect();
+    write_output(cli, config, layout, &all_blocks, &all_inject_blocks)
+}
 
-    writer::write_markdown_and_summary(
-        &all_blocks,
-        Path::new(target_dir),
-        &summary_path,
-        cli.dry_run,
-        cli.mirror_root_summary,
-    )?;
-    if cli.generate_readme {
-        let readme_path = Path::new("README.md");
-        writer::write_readme(&all_readme_blocks, readme_path, cli.dry_run)?;
-        println!(
-            "README.md wurde aus {} Block(s) generiert.",
-            all_readme_blocks.len()
-        );
+/// Erzeugt die Dokumentation einmal vollständig und danach bei jeder Änderung
+/// unter `paths.source` neu, wobei nur die geänderten Dateien neu geparst werden.
+/// Verschwundene oder verschobene Blöcke fallen dabei aus ihren alten Kapiteln;
+/// Kapitel ohne Blöcke werden über das Manifest gelöscht.
+fn watch_sources(cli: &Cli, config: &RustdocmdConfig, layout: &BookLayout) -> Result<()> {
+    let source_dir = Path::new(&config.paths.source);
+    let mut sources = SourceBlocks::new();
+    scan_sources(source_dir, &mut sources)?;
+    if let Err(e) = write_sources(cli, config, layout, &sources) {
+        eprintln!("Fehler: {:#}", e);
     }
-    if !cli.dry_run {
-        let root_summary = Path::new(target_dir).parent().map(|p| p.join("SUMMARY.md"));
-        if !summary_path.exists() {
-            if let Some(root) = root_summary {
-                if root.exists() {
-                    fs::copy(&root, &summary_path)?;
-                }
-            }
+    watch::watch(source_dir, |changed| {
+        update_sources(&mut sources, changed);
+        write_sources(cli, config, layout, &sources)
+    })
+}
+
+/// Plant alle Ausgaben und wendet sie an (bzw. zeigt/prüft sie bei `--dry-run`/`--check`)
+fn write_output(
+    cli: &Cli,
+    config: &RustdocmdConfig,
+    layout: &BookLayout,
+    all_blocks: &[MarkerBlock],
+    all_inject_blocks: &[InjectBlock],
+) -> Result<()> {
+    let mut plan = OutputPlan::default();
+    writer::write_markdown_and_summary(all_blocks, layout, &config.summary, &mut plan)?;
+    // Bereiche in README.md (nur mit --generate-readme) und den Dateien aus [inject] füllen
+    let normalize = |file: &str| file.trim_start_matches("./").to_string();
+    let mut inject_files: Vec<String> = config.inject.files.iter().map(|f| normalize(f)).collect();
+    if cli.generate_readme && !inject_files.iter().any(|f| f == README_FILE) {
+        inject_files.push(README_FILE.to_string());
+    }
+    for block in all_inject_blocks {
+        let file = normalize(&block.file);
+        if file != README_FILE && !inject_files.contains(&file) {
+            eprintln!(
+                "Warnung: {} ist nicht in [inject] files eingetragen, Block wird ignoriert ({})",
+                block.file, block.location
+            );
         }
     }
+    for file in &inject_files {
+        let blocks: Vec<&InjectBlock> = all_inject_blocks
+            .iter()
+            .filter(|b| normalize(&b.file) == *file)
+            .collect();
+        let is_readme = file == README_FILE;
+        let default_config = ReadmeConfig::default();
+        let readme_config = if is_readme {
+            &config.readme
+        } else {
+            &default_config
+        };
+        writer::write_injections(
+            &blocks,
+            Path::new(file),
+            is_readme,
+            readme_config,
+            &mut plan,
+        )?;
+        println!("{} wurde aus {} Block(s) aktualisiert.", file, blocks.len());
+    }
+
+    // Diffs nur im Terminal einfärben (und nicht mit NO_COLOR)
+    let colour = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    if cli.check {
+        let summary = plan.check(colour)?;
+        if summary.updated() > 0 {
+            bail!(
+                "{} Datei(en) nicht aktuell, bitte rustdocmd ausführen",
+                summary.updated()
+            );
+        }
+        println!("Alle erzeugten Dateien sind aktuell.");
+        return Ok(());
+    }
+    if cli.dry_run {
+        plan.print_dry_run(colour)?;
+    } else {
+        let summary = plan.apply()?;
+        println!(
+            "{} Datei(en) aktualisiert ({}).",
+            summary.updated(),
+            summary
+        );
+    }
     println!("{} Marker-Blöcke verarbeitet.", all_blocks.len());
     Ok(())
 }
+
+/// Baut das mdBook im Buch-Root (wie `mdbook build`)
+fn build_book(layout: &BookLayout) -> Result<()> {
+    let book = mdbook::MDBook::load(&layout.root)
+        .map_err(|e| anyhow!("mdBook in {} nicht ladbar: {:#}", layout.root.display(), e))?;
+    book.build()
+        .map_err(|e| anyhow!("mdBook-Build fehlgeschlagen: {:#}", e))?;
+    println!("mdBook gebaut: {}", book.build_dir_for("html").display());
+    Ok(())
+}
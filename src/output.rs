@@ -0,0 +1,357 @@
+use similar::TextDiff;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Eine geplante Änderung an einer Ausgabedatei
+#[derive(Debug, Clone)]
+pub enum FileOp {
+    /// Datei mit `content` schreiben; `sources` nennt die Herkunft (z.B. src/foo.rs:3-9)
+    Write {
+        path: PathBuf,
+        content: String,
+        sources: String,
+    },
+    /// Datei löschen und leer gewordene Ordner bis (ausschließlich) `root` entfernen
+    Remove { path: PathBuf, root: PathBuf },
+}
+
+impl FileOp {
+    /// Vergleicht die Änderung mit der Datei auf der Platte; liefert den Status
+    /// und den bisherigen Inhalt
+    fn status(&self) -> io::Result<(FileStatus, Option<Vec<u8>>)> {
+        let path = match self {
+            FileOp::Write { path, .. } | FileOp::Remove { path, .. } => path,
+        };
+        let old = match fs::read(path) {
+            Ok(old) => Some(old),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let status = match (self, &old) {
+            (FileOp::Remove { .. }, Some(_)) => FileStatus::Deleted,
+            (FileOp::Remove { .. }, None) => FileStatus::Unchanged,
+            (FileOp::Write { .. }, None) => FileStatus::Created,
+            (FileOp::Write { content, .. }, Some(old)) if old == content.as_bytes() => {
+                FileStatus::Unchanged
+            }
+            (FileOp::Write { .. }, Some(_)) => FileStatus::Changed,
+        };
+        Ok((status, old))
+    }
+
+    /// Wie `status`, aber mit unified diff statt bisherigem Inhalt (leer, wenn
+    /// sich nichts ändert)
+    fn compare(&self, colour: bool) -> io::Result<(FileStatus, String)> {
+        let (status, old) = self.status()?;
+        if status == FileStatus::Unchanged {
+            return Ok((status, String::new()));
+        }
+        let (path, new) = match self {
+            FileOp::Write { path, content, .. } => (path, content.as_str()),
+            FileOp::Remove { path, .. } => (path, ""),
+        };
+        let old = old.map(|old| String::from_utf8_lossy(&old).into_owned());
+        let name = path.display().to_string();
+        let diff = TextDiff::from_lines(old.as_deref().unwrap_or(""), new)
+            .unified_diff()
+            .header(
+                if old.is_some() { &name } else { "/dev/null" },
+                if status == FileStatus::Deleted {
+                    "/dev/null"
+                } else {
+                    &name
+                },
+            )
+            .to_string();
+        Ok((status, if colour { colourize(&diff) } else { diff }))
+    }
+}
+
+/// Färbt einen unified diff für das Terminal ein
+fn colourize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let code = if line.starts_with("+++") || line.starts_with("---") {
+                "1"
+            } else if line.starts_with('+') {
+                "32"
+            } else if line.starts_with('-') {
+                "31"
+            } else if line.starts_with("@@") {
+                "36"
+            } else {
+                return format!("{}\n", line);
+            };
+            format!("\x1b[{}m{}\x1b[0m\n", code, line)
+        })
+        .collect()
+}
+
+/// Ergebnis des Vergleichs einer geplanten Änderung mit der Platte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Created,
+    Changed,
+    Unchanged,
+    Deleted,
+}
+
+/// Anzahl der neuen, geänderten, unveränderten und gelöschten Dateien
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChangeSummary {
+    pub created: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+}
+
+impl ChangeSummary {
+    fn add(&mut self, status: FileStatus) {
+        match status {
+            FileStatus::Created => self.created += 1,
+            FileStatus::Changed => self.changed += 1,
+            FileStatus::Unchanged => self.unchanged += 1,
+            FileStatus::Deleted => self.deleted += 1,
+        }
+    }
+
+    /// Anzahl der Dateien, die geschrieben oder gelöscht werden (wurden)
+    pub fn updated(&self) -> usize {
+        self.created + self.changed + self.deleted
+    }
+}
+
+impl fmt::Display for ChangeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} neu, {} geändert, {} unverändert, {} gelöscht",
+            self.created, self.changed, self.unchanged, self.deleted
+        )
+    }
+}
+
+/// Alle Änderungen eines Laufs. Die Writer füllen den Plan nur; erst `apply`
+/// verändert die Dateien, `check` vergleicht ihn mit dem Stand auf der Platte.
+#[derive(Debug, Default)]
+pub struct OutputPlan {
+    pub ops: Vec<FileOp>,
+}
+
+impl OutputPlan {
+    pub fn write(&mut self, path: &Path, content: String, sources: String) {
+        self.ops.push(FileOp::Write {
+            path: path.to_path_buf(),
+            content,
+            sources,
+        });
+    }
+
+    pub fn remove(&mut self, path: &Path, root: &Path) {
+        self.ops.push(FileOp::Remove {
+            path: path.to_path_buf(),
+            root: root.to_path_buf(),
+        });
+    }
+
+    /// Gibt aus, was geschrieben/entfernt würde (`--dry-run`), jeweils mit
+    /// Diff gegen den aktuellen Stand und einer Zusammenfassung
+    pub fn print_dry_run(&self, colour: bool) -> io::Result<ChangeSummary> {
+        let mut summary = ChangeSummary::default();
+        for op in &self.ops {
+            let (status, diff) = op.compare(colour)?;
+            match op {
+                FileOp::Write {
+                    path,
+                    content,
+                    sources,
+                } if sources.is_empty() => {
+                    println!(
+                        "[dry-run] write {} ({} bytes)",
+                        path.display(),
+                        content.len()
+                    )
+                }
+                FileOp::Write {
+                    path,
+                    content,
+                    sources,
+                } => println!(
+                    "[dry-run] write {} ({} bytes) <- {}",
+                    path.display(),
+                    content.len(),
+                    sources
+                ),
+                FileOp::Remove { path, .. } => println!("[dry-run] remove {}", path.display()),
+            }
+            print!("{}", diff);
+            summary.add(status);
+        }
+        println!("[dry-run] {}", summary);
+        Ok(summary)
+    }
+
+    /// Vergleicht den Plan mit den Dateien auf der Platte (`--check`), nennt
+    /// jede Datei, die sich ändern oder gelöscht würde, samt Diff
+    pub fn check(&self, colour: bool) -> io::Result<ChangeSummary> {
+        let mut summary = ChangeSummary::default();
+        for op in &self.ops {
+            let (status, diff) = op.compare(colour)?;
+            let path = match op {
+                FileOp::Write { path, .. } | FileOp::Remove { path, .. } => path.display(),
+            };
+            match status {
+                FileStatus::Created => println!("fehlt: {}", path),
+                FileStatus::Changed => println!("veraltet: {}", path),
+                FileStatus::Deleted => println!("würde gelöscht: {}", path),
+                FileStatus::Unchanged => {}
+            }
+            print!("{}", diff);
+            summary.add(status);
+        }
+        println!("{}", summary);
+        Ok(summary)
+    }
+
+    /// Schreibt und löscht die Dateien des Plans als eine Transaktion: alle neuen
+    /// Inhalte werden zuerst in temporäre Dateien geschrieben und erst danach
+    /// per Umbenennen übernommen. Schlägt ein Schritt fehl, wird der vorherige
+    /// Stand wiederhergestellt. Unveränderte Dateien werden nicht angefasst,
+    /// damit ihr mtime erhalten bleibt.
+    pub fn apply(&self) -> io::Result<ChangeSummary> {
+        let mut summary = ChangeSummary::default();
+        let mut tx = Transaction::default();
+        match self.stage(&mut tx, &mut summary).and_then(|_| tx.commit()) {
+            Ok(()) => {
+                tx.finish();
+                Ok(summary)
+            }
+            Err(e) => {
+                tx.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    /// Schreibt die neuen Inhalte in temporäre Dateien neben den Zielen
+    fn stage(&self, tx: &mut Transaction, summary: &mut ChangeSummary) -> io::Result<()> {
+        for op in &self.ops {
+            let (status, _) = op.status()?;
+            match op {
+                _ if status == FileStatus::Unchanged => {}
+                FileOp::Write { path, content, .. } => {
+                    if let Some(parent) = path.parent() {
+                        tx.create_dir_all(parent)?;
+                    }
+                    let temp = sibling(path, "tmp");
+                    tx.staged.push((temp.clone(), path.clone()));
+                    fs::write(&temp, content)?;
+                }
+                FileOp::Remove { path, root } => tx.removed.push((path.clone(), root.clone())),
+            }
+            summary.add(status);
+        }
+        Ok(())
+    }
+}
+
+/// Zustand von `OutputPlan::apply`, um bei Fehlern zurückrollen zu können
+#[derive(Debug, Default)]
+struct Transaction {
+    created_dirs: Vec<PathBuf>,       // neu angelegte Ordner, äußere zuerst
+    staged: Vec<(PathBuf, PathBuf)>,  // (temporäre Datei, Ziel)
+    removed: Vec<(PathBuf, PathBuf)>, // (zu löschende Datei, Wurzel)
+    committed: Vec<(PathBuf, Option<PathBuf>)>, // (Ziel, Sicherung des alten Inhalts)
+}
+
+impl Transaction {
+    /// Wie `fs::create_dir_all`, merkt sich aber die neu angelegten Ordner
+    fn create_dir_all(&mut self, dir: &Path) -> io::Result<()> {
+        let mut missing: Vec<&Path> = dir.ancestors().take_while(|d| !d.exists()).collect();
+        missing.retain(|d| !d.as_os_str().is_empty());
+        for d in missing.into_iter().rev() {
+            fs::create_dir(d)?;
+            self.created_dirs.push(d.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Übernimmt die temporären Dateien und Löschungen; alte Inhalte werden
+    /// dabei nur beiseitegelegt
+    fn commit(&mut self) -> io::Result<()> {
+        for (temp, path) in &self.staged {
+            let backup = self.backup(path)?;
+            self.committed.push((path.clone(), backup));
+            fs::rename(temp, path)?;
+        }
+        for (path, _) in &self.removed {
+            let backup = self.backup(path)?;
+            self.committed.push((path.clone(), backup));
+        }
+        Ok(())
+    }
+
+    /// Benennt eine vorhandene Datei in ihre Sicherung um
+    fn backup(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let backup = sibling(path, "bak");
+        fs::rename(path, &backup)?;
+        Ok(Some(backup))
+    }
+
+    /// Entfernt die Sicherungen und leer gewordene Ordner gelöschter Kapitel
+    fn finish(self) {
+        for (_, backup) in &self.committed {
+            if let Some(backup) = backup {
+                let _ = fs::remove_file(backup);
+            }
+        }
+        for (path, root) in &self.removed {
+            remove_empty_parents(path, root);
+        }
+    }
+
+    /// Stellt den Stand vor `apply` wieder her
+    fn rollback(self) {
+        for (path, backup) in self.committed.iter().rev() {
+            let _ = fs::remove_file(path);
+            if let Some(backup) = backup {
+                if let Err(e) = fs::rename(backup, path) {
+                    eprintln!(
+                        "Warnung: {} konnte nicht wiederhergestellt werden (Sicherung: {}): {}",
+                        path.display(),
+                        backup.display(),
+                        e
+                    );
+                }
+            }
+        }
+        for (temp, _) in &self.staged {
+            let _ = fs::remove_file(temp);
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Versteckte Hilfsdatei neben `path`, z.B. `.intro.md.rustdocmd-tmp`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.rustdocmd-{}", name, suffix))
+}
+
+/// Entfernt leer gewordene Verzeichnisse oberhalb von `file` bis (ausschließlich) `root`
+fn remove_empty_parents(file: &Path, root: &Path) {
+    let mut dir = file.parent();
+    while let Some(d) = dir {
+        if d == root || !d.starts_with(root) || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
@@ -1,98 +1,642 @@
+use anyhow::{anyhow, Result};
+use proc_macro2::{Delimiter, Group, Punct, Spacing, TokenStream, TokenTree};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Herkunft eines Blocks: Quelldatei und Zeilen des öffnenden/schließenden Markers
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}",
+            self.path.display(),
+            self.start_line,
+            self.end_line
+        )
+    }
+}
+
+/// Eine Zeile Doc-Text samt Zeilennummer in der Quelldatei
+#[derive(Debug, Clone)]
+pub struct DocLine {
+    pub line: usize,
+    pub text: String,
+}
+
+/// Zusammenhängender Doc-Kommentar und das Element, an dem er hängt (z.B. "fn main")
+#[derive(Debug, Clone)]
+pub struct DocComment {
+    pub path: PathBuf,
+    pub item: String,
+    pub lines: Vec<DocLine>,
+}
+
+impl DocComment {
+    pub fn texts(&self) -> Vec<&str> {
+        self.lines.iter().map(|l| l.text.as_str()).collect()
+    }
+
+    /// Herkunft der Doc-Zeilen `first..=last`
+    fn location(&self, first: usize, last: usize) -> SourceLocation {
+        SourceLocation {
+            path: self.path.clone(),
+            start_line: self.lines[first].line,
+            end_line: self.lines[last].line,
+        }
+    }
+}
+
+/// Hierarchische Kapitelnummer, z.B. 2.1.3 aus `<config-paths.md(2.1.3)>`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChapterOrder(pub Vec<usize>);
+
+impl ChapterOrder {
+    /// Verschachtelungstiefe: 0 für `2`, 1 für `2.1`, ...
+    pub fn depth(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    /// Nummer des übergeordneten Kapitels (`2.1` für `2.1.3`)
+    pub fn parent(&self) -> Option<ChapterOrder> {
+        (self.0.len() > 1).then(|| ChapterOrder(self.0[..self.0.len() - 1].to_vec()))
+    }
+}
+
+impl std::str::FromStr for ChapterOrder {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('.')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(ChapterOrder)
+    }
+}
+
+impl fmt::Display for ChapterOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(usize::to_string).collect();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct ReadmeBlock {
-    pub content: String, // Inhalt für README.md
+pub struct MarkerBlock {
+    pub item: String,                                 // z.B. "fn main"
+    pub location: SourceLocation,                     // z.B. src/foo.rs:12-40
+    pub target_md: String,                            // z.B. "test.md"
+    pub order: Option<ChapterOrder>,                  // z.B. 1 oder 2.1.3
+    pub fragment: Option<usize>,                      // z.B. 2 bei <file.md(1)#2>
+    pub attributes: BTreeMap<String, Option<String>>, // z.B. title="Intro", draft
+    pub source_ref: String,                           // z.B. "code.md" oder leer
+    pub content: String,                              // extrahierter Inhalt
 }
 
-/// Extrahiert alle <readme>...</readme>-Blöcke aus Rustdoc-Kommentaren
-pub fn extract_readme_blocks(source: &str) -> Vec<ReadmeBlock> {
-    let doc = extract_rustdoc_comments(source);
-    let lines: Vec<&str> = doc.lines().collect();
-    let re_open = Regex::new(r"^\s*<readme>\s*$").unwrap();
-    let re_close = Regex::new(r"^\s*</readme>\s*$").unwrap();
+impl MarkerBlock {
+    /// Wert eines Attributs wie `title="Getting Started"`
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).and_then(|v| v.as_deref())
+    }
+
+    /// Ob ein Attribut gesetzt ist, mit oder ohne Wert (z.B. `draft`)
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.contains_key(name)
+    }
+}
+
+/// Attribute, die öffnende Marker tragen dürfen; andere erzeugen eine Warnung
+const KNOWN_ATTRIBUTES: &[&str] = &[
+    "title", "draft", "parent", "audience", "weight", "after", "before",
+];
+
+/// Tags der Inject-Blöcke (`extract_inject_blocks`); sie sind keine Kapitel
+const INJECT_TAGS: &[&str] = &["readme", "inject"];
+
+/// Extrahiert alle Marker-Blöcke aus Rustdoc-Kommentaren (blockweise, Zeilenumbrüche erlaubt)
+/// Marker öffnen und schließen innerhalb desselben Doc-Kommentars, also am selben Element.
+pub fn extract_marker_blocks(docs: &[DocComment]) -> Vec<MarkerBlock> {
+    // Öffnende Zeile: <file.md(1)>, <file.md(2.1.3)> bzw. <ordner/file.md(1)#2>,
+    // optional mit Attributen (<faq.md title="FAQ" after="intro.md" draft>) und
+    // gefolgt von "source_ref"
+    let re_open = Regex::new(
+        r#"^\s*<([\w\-.][\w\-./]*)(?:\((\d+(?:\.\d+)*)\))?(?:#(\d+))?((?:\s+[\w\-]+(?:=(?:"[^"]*"|[^\s">]+))?)*)\s*>\s*(?:\"([^\"]+)\")?\s*$"#,
+    )
+    .unwrap();
     let mut blocks = Vec::new();
-    let mut i = 0;
-    while i < lines.len() {
-        if re_open.is_match(lines[i]) {
-            let mut j = i + 1;
-            while j < lines.len() && !re_close.is_match(lines[j]) {
-                j += 1;
-            }
-            if j < lines.len() {
-                let content = lines[i + 1..j].join("\n").trim().to_string();
-                blocks.push(ReadmeBlock { content });
-                i = j + 1;
-                continue;
+    for doc in docs {
+        let lines = doc.texts();
+        let mut i = 0;
+        while i < lines.len() {
+            if let Some(cap) = re_open.captures(lines[i]) {
+                let tag = cap[1].to_string();
+                // HTML wie <div align="center"> oder <details open> ist kein Kapitel
+                if !tag.ends_with(".md") && !INJECT_TAGS.contains(&tag.as_str()) {
+                    i += 1;
+                    continue;
+                }
+                let order = cap
+                    .get(2)
+                    .and_then(|m| m.as_str().parse::<ChapterOrder>().ok());
+                let fragment = cap.get(3).and_then(|m| m.as_str().parse::<usize>().ok());
+                let attributes = parse_attributes(&cap[4]);
+                let source_ref = cap
+                    .get(5)
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default();
+                // Suche nach passendem schließenden Tag ab der nächsten Zeile
+                let re_close =
+                    Regex::new(&format!(r#"^\s*</{}>\s*$"#, regex::escape(&tag))).unwrap();
+                let mut j = i + 1;
+                while j < lines.len() && !re_close.is_match(lines[j]) {
+                    j += 1;
+                }
+                if j < lines.len() && INJECT_TAGS.contains(&tag.as_str()) {
+                    // <readme>/<inject>-Block: gehört nicht ins Buch
+                    i = j + 1;
+                    continue;
+                } else if j < lines.len() {
+                    // schließendes Tag gefunden
+                    let content = dedent_block(&lines[i + 1..j]);
+                    let location = doc.location(i, j);
+                    let attributes = check_attributes(&tag, attributes, &location);
+                    blocks.push(MarkerBlock {
+                        item: doc.item.clone(),
+                        location,
+                        target_md: tag,
+                        order,
+                        fragment,
+                        attributes,
+                        source_ref,
+                        content,
+                    });
+                    i = j + 1; // weiter nach dem schließenden Tag
+                    continue;
+                } else {
+                    // kein schließendes Tag gefunden -> ignoriere diesen offenen Marker
+                }
             }
+            i += 1;
         }
-        i += 1;
     }
     blocks
 }
-use regex::Regex;
 
+/// Zerlegt die Attributliste eines öffnenden Markers, z.B. ` after="intro.md" draft`
+fn parse_attributes(list: &str) -> Vec<(String, Option<String>)> {
+    let re_attr = Regex::new(r#"([\w\-]+)(?:=(?:"([^"]*)"|([^\s">]+)))?"#).unwrap();
+    re_attr
+        .captures_iter(list)
+        .map(|cap| {
+            let value = cap.get(2).or(cap.get(3)).map(|m| m.as_str().to_string());
+            (cap[1].to_string(), value)
+        })
+        .collect()
+}
+
+/// Übernimmt die Attribute eines Markers und warnt bei unbekannten, doppelten
+/// oder ungültigen Angaben
+fn check_attributes(
+    tag: &str,
+    attributes: Vec<(String, Option<String>)>,
+    location: &SourceLocation,
+) -> BTreeMap<String, Option<String>> {
+    let mut checked = BTreeMap::new();
+    for (key, value) in attributes {
+        let problem = if !KNOWN_ATTRIBUTES.contains(&key.as_str()) {
+            Some("unbekanntes Attribut")
+        } else if checked.contains_key(&key) {
+            Some("doppeltes Attribut")
+        } else if key != "draft" && value.is_none() {
+            Some("Attribut ohne Wert")
+        } else if key == "weight" && value.as_deref().is_some_and(|v| v.parse::<i64>().is_err()) {
+            Some("keine Zahl in Attribut")
+        } else {
+            None
+        };
+        match problem {
+            Some(problem) => {
+                eprintln!("Warnung: {} '{}' in <{}> ({})", problem, key, tag, location)
+            }
+            None => {
+                checked.insert(key, value);
+            }
+        }
+    }
+    checked
+}
+
+/// Block für einen Bereich einer bestehenden Datei (`<readme>`, `<inject>`)
 #[derive(Debug, Clone)]
-pub struct MarkerBlock {
-    pub target_md: String,    // z.B. "test.md"
-    pub order: Option<usize>, // z.B. 1
-    pub source_ref: String,   // z.B. "code.md" oder leer
-    pub content: String,      // extrahierter Inhalt
+pub struct InjectBlock {
+    pub item: String,                // Element, an dem der Doc-Kommentar hängt
+    pub location: SourceLocation,    // Herkunft, z.B. src/foo.rs:12-40
+    pub file: String,                // Zieldatei, z.B. README.md oder CONTRIBUTING.md
+    pub region: String,              // Bereich in der Zieldatei, leer ohne Namen
+    pub order: Option<ChapterOrder>, // Reihenfolge im Bereich, z.B. <readme(2)>
+    pub section: Option<String>,     // Abschnitt mit eigener Überschrift (section="...")
+    pub content: String,             // Inhalt für den Bereich
 }
 
-/// Extrahiert alle Marker-Blöcke aus Rustdoc-Kommentaren (blockweise, Zeilenumbrüche erlaubt)
-pub fn extract_marker_blocks(source: &str) -> Vec<MarkerBlock> {
-    let doc = extract_rustdoc_comments(source);
-    let lines: Vec<&str> = doc.lines().collect();
-    // Öffnende Zeile: <file.md(1)> optional gefolgt von "source_ref"
-    let re_open = Regex::new(r#"^\s*<([\w\-.]+)(?:\((\d+)\))?>\s*(?:\"([^\"]+)\")?\s*$"#).unwrap();
+/// Zieldatei von `<readme>`-Blöcken
+pub const README_FILE: &str = "README.md";
+
+/// Extrahiert alle Blöcke, die in Bereiche bestehender Dateien eingesetzt werden:
+/// `<inject file="CONTRIBUTING.md" region="build">` ... `</inject>` sowie
+/// `<readme name="usage">` ... `</readme>` als Kurzform für README.md.
+/// Eine Ordnungsnummer (`<readme(2)>`) und `section="..."` steuern den Aufbau.
+pub fn extract_inject_blocks(docs: &[DocComment]) -> Vec<InjectBlock> {
+    let re_open = Regex::new(
+        r#"^\s*<(readme|inject)(?:\((\d+(?:\.\d+)*)\))?((?:\s+[\w\-]+(?:=(?:"[^"]*"|[^\s">]+))?)*)\s*>\s*$"#,
+    )
+    .unwrap();
     let mut blocks = Vec::new();
+    for doc in docs {
+        let lines = doc.texts();
+        let mut i = 0;
+        while i < lines.len() {
+            if let Some(cap) = re_open.captures(lines[i]) {
+                let tag = &cap[1];
+                let close = format!("</{}>", tag);
+                let mut j = i + 1;
+                while j < lines.len() && lines[j].trim() != close {
+                    j += 1;
+                }
+                if j < lines.len() {
+                    let location = doc.location(i, j);
+                    let mut file = (tag == "readme").then(|| README_FILE.to_string());
+                    let mut region = String::new();
+                    let mut section = None;
+                    for (key, value) in parse_attributes(&cap[3]) {
+                        match (tag, key.as_str(), value) {
+                            ("readme", "name", Some(value)) | ("inject", "region", Some(value)) => {
+                                region = value
+                            }
+                            ("inject", "file", Some(value)) => file = Some(value),
+                            (_, "section", Some(value)) => section = Some(value),
+                            _ => eprintln!(
+                                "Warnung: unbekanntes Attribut '{}' in <{}> ({})",
+                                key, tag, location
+                            ),
+                        }
+                    }
+                    match file {
+                        Some(file) => blocks.push(InjectBlock {
+                            item: doc.item.clone(),
+                            location,
+                            file,
+                            region,
+                            order: cap.get(2).and_then(|m| m.as_str().parse().ok()),
+                            section,
+                            content: dedent_block(&lines[i + 1..j]),
+                        }),
+                        None => eprintln!(
+                            "Warnung: <inject> ohne file=\"...\" wird ignoriert ({})",
+                            location
+                        ),
+                    }
+                    i = j + 1;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+    }
+    blocks
+}
+
+/// Zerlegt die Quelle mit dem Rust-Tokenizer und liefert alle Doc-Kommentare
+/// (`///`, `//!`, `/** */`, `/*! */`, `#[doc = "..."]`, `#[doc = include_str!("...")]`)
+/// gruppiert nach dem Element, an dem sie hängen. Kommentar-ähnlicher Text in
+/// String-Literalen und gewöhnliche Kommentare werden dabei nicht erfasst.
+/// Entfernt wird nur das Kommentar-Präfix und das eine übliche Leerzeichen danach,
+/// damit die Einrückung (verschachtelte Listen, Codeblöcke, YAML/TOML) erhalten bleibt.
+/// `include_str!`-Pfade werden relativ zu `source_path` aufgelöst.
+pub fn extract_rustdoc_comments(source: &str, source_path: &Path) -> Result<Vec<DocComment>> {
+    let tokens: TokenStream = source.parse().map_err(|e: proc_macro2::LexError| {
+        anyhow!(
+            "{}:{} ist kein gültiger Rust-Code: {}",
+            source_path.display(),
+            e.span().start().line,
+            e
+        )
+    })?;
+    let mut docs = Vec::new();
+    collect_docs(tokens, &file_item(source_path), source_path, &mut docs);
+    // Spans werden nicht mehr gebraucht; gibt die Quelltext-Kopie des Tokenizers frei
+    proc_macro2::extra::invalidate_current_thread_spans();
+    Ok(docs)
+}
+
+/// Name des Moduls, das eine Datei bildet (`main.rs`/`lib.rs` = crate)
+fn file_item(source_path: &Path) -> String {
+    let stem = source_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    match stem {
+        "main" | "lib" => "crate".to_string(),
+        "mod" => source_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| format!("mod {}", n.to_string_lossy()))
+            .unwrap_or_else(|| "crate".to_string()),
+        _ => format!("mod {}", stem),
+    }
+}
+
+/// Sammelt die Doc-Kommentare eines Token-Streams. `owner` ist das umschließende
+/// Element, dem innere Doc-Kommentare (`//!`, `#![doc]`) zugeordnet werden.
+fn collect_docs(stream: TokenStream, owner: &str, source_path: &Path, docs: &mut Vec<DocComment>) {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let first = docs.len();
+    let mut inner: Vec<DocLine> = Vec::new();
+    let mut pending: Vec<DocLine> = Vec::new();
     let mut i = 0;
-    while i < lines.len() {
-        if let Some(cap) = re_open.captures(lines[i]) {
-            let tag = cap[1].to_string();
-            let order = cap.get(2).and_then(|m| m.as_str().parse::<usize>().ok());
-            let source_ref = cap
-                .get(3)
-                .map(|m| m.as_str().to_string())
-                .unwrap_or_default();
-            // Suche nach passendem schließenden Tag ab der nächsten Zeile
-            let re_close = Regex::new(&format!(r#"^\s*</{}>\s*$"#, regex::escape(&tag))).unwrap();
-            let mut j = i + 1;
-            while j < lines.len() && !re_close.is_match(lines[j]) {
-                j += 1;
+    while i < tokens.len() {
+        // Attribut: `#[...]` oder `#![...]`
+        if let TokenTree::Punct(pound) = &tokens[i] {
+            if pound.as_char() == '#' {
+                let is_inner =
+                    matches!(&tokens.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                let at = i + 1 + usize::from(is_inner);
+                if let Some(TokenTree::Group(group)) = tokens.get(at) {
+                    if group.delimiter() == Delimiter::Bracket {
+                        let lines = doc_attr_lines(pound, group, source_path);
+                        if is_inner {
+                            inner.extend(lines);
+                        } else {
+                            pending.extend(lines);
+                        }
+                        i = at + 1;
+                        continue;
+                    }
+                }
             }
-            if j < lines.len() {
-                // schließendes Tag gefunden
-                let content = lines[i + 1..j].join("\n").trim().to_string();
-                blocks.push(MarkerBlock {
-                    target_md: tag,
-                    order,
-                    source_ref,
-                    content,
-                });
-                i = j + 1; // weiter nach dem schließenden Tag
-                continue;
-            } else {
-                // kein schließendes Tag gefunden -> ignoriere diesen offenen Marker
+        }
+        let end = item_end(&tokens, i);
+        let item = describe_item(&tokens[i..end]);
+        if !pending.is_empty() {
+            docs.push(DocComment {
+                path: source_path.to_path_buf(),
+                item: item.clone(),
+                lines: std::mem::take(&mut pending),
+            });
+        }
+        for token in &tokens[i..end] {
+            if let TokenTree::Group(group) = token {
+                collect_docs(group.stream(), &item, source_path, docs);
             }
         }
-        i += 1;
+        i = end;
+    }
+    if !inner.is_empty() {
+        let doc = DocComment {
+            path: source_path.to_path_buf(),
+            item: owner.to_string(),
+            lines: inner,
+        };
+        docs.insert(first, doc);
+    }
+    // Doc-Kommentar ohne nachfolgendes Element (z.B. am Dateiende)
+    if !pending.is_empty() {
+        docs.push(DocComment {
+            path: source_path.to_path_buf(),
+            item: owner.to_string(),
+            lines: pending,
+        });
     }
-    blocks
 }
 
-pub fn extract_rustdoc_comments(source: &str) -> String {
-    source
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim_start();
-            if let Some(rest) = line.strip_prefix("///") {
-                Some(rest.trim())
-            } else if let Some(rest) = line.strip_prefix("//!") {
-                Some(rest.trim())
-            } else {
-                None
+/// Ende des Elements ab `start`: nach `;`, nach einem `,` außerhalb von
+/// Generics (`<T, U>`) und where-Klauseln oder einschließlich des ersten
+/// `{ ... }`-Rumpfs. Nach einem `=` (z.B. `A = 1 << 2,`) zählen `<`/`>` als Operatoren.
+fn item_end(tokens: &[TokenTree], start: usize) -> usize {
+    let mut angles = 0usize;
+    let mut in_where = false;
+    let mut in_expr = false;
+    let mut end = start;
+    while end < tokens.len() {
+        end += 1;
+        match &tokens[end - 1] {
+            TokenTree::Punct(p) => match p.as_char() {
+                ';' => break,
+                ',' if angles == 0 && !in_where => break,
+                '=' if angles == 0 && is_assignment(tokens, end - 1) => in_expr = true,
+                _ if in_expr => {}
+                _ => angles = angle_depth(angles, tokens, end - 1),
+            },
+            TokenTree::Ident(ident) if ident == "where" && angles == 0 => in_where = true,
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => break,
+            _ => {}
+        }
+    }
+    end
+}
+
+/// Tiefe in spitzen Klammern nach `tokens[i]`; `->` und `=>` schließen keine
+fn angle_depth(depth: usize, tokens: &[TokenTree], i: usize) -> usize {
+    let joint_before = |chars: &[char]| {
+        matches!(i.checked_sub(1).map(|k| &tokens[k]),
+            Some(TokenTree::Punct(p)) if p.spacing() == Spacing::Joint && chars.contains(&p.as_char()))
+    };
+    match &tokens[i] {
+        TokenTree::Punct(p) if p.as_char() == '<' => depth + 1,
+        TokenTree::Punct(p) if p.as_char() == '>' && !joint_before(&['-', '=']) => {
+            depth.saturating_sub(1)
+        }
+        _ => depth,
+    }
+}
+
+/// Ob `tokens[i]` ein alleinstehendes `=` ist (nicht Teil von `==`, `<=`, `=>` ...)
+fn is_assignment(tokens: &[TokenTree], i: usize) -> bool {
+    let TokenTree::Punct(p) = &tokens[i] else {
+        return false;
+    };
+    let after_joint = matches!(i.checked_sub(1).map(|k| &tokens[k]),
+        Some(TokenTree::Punct(prev)) if prev.spacing() == Spacing::Joint);
+    p.as_char() == '=' && p.spacing() == Spacing::Alone && !after_joint
+}
+
+/// Kurzbeschreibung eines Elements, z.B. "fn main", "struct Guide", "impl Writer"
+fn describe_item(tokens: &[TokenTree]) -> String {
+    const KINDS: &[&str] = &[
+        "fn",
+        "struct",
+        "enum",
+        "union",
+        "trait",
+        "mod",
+        "const",
+        "static",
+        "type",
+        "impl",
+        "macro_rules",
+    ];
+    // Bezeichner vor dem Rumpf, ohne die in Generics (`impl<T> Foo for Bar<T>`)
+    let mut idents: Vec<String> = Vec::new();
+    let mut angles = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => break,
+            TokenTree::Ident(ident) if angles == 0 => idents.push(ident.to_string()),
+            _ => angles = angle_depth(angles, tokens, i),
+        }
+    }
+    let Some(pos) = idents.iter().position(|i| KINDS.contains(&i.as_str())) else {
+        // Feld, Variante o.ä.: erster Bezeichner nach der Sichtbarkeit
+        return idents.into_iter().find(|i| i != "pub").unwrap_or_default();
+    };
+    let kind = &idents[pos];
+    let name = if kind == "impl" {
+        // bei `impl Trait for Typ` zählt der Typ
+        idents[pos + 1..]
+            .iter()
+            .take_while(|i| *i != "where")
+            .last()
+    } else {
+        idents.get(pos + 1)
+    };
+    match name {
+        Some(name) => format!("{} {}", kind, name),
+        None => kind.clone(),
+    }
+}
+
+/// Liefert die Doc-Zeilen eines Attributs, falls es ein `doc = ...`-Attribut ist
+fn doc_attr_lines(pound: &Punct, group: &Group, source_path: &Path) -> Vec<DocLine> {
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    let value = match tokens.as_slice() {
+        [TokenTree::Ident(doc), TokenTree::Punct(eq), value @ ..]
+            if doc == "doc" && eq.as_char() == '=' =>
+        {
+            value
+        }
+        _ => return Vec::new(),
+    };
+    let line = pound.span().start().line;
+    let text = match value {
+        [TokenTree::Literal(lit)] => str_literal_value(lit),
+        [TokenTree::Ident(mac), TokenTree::Punct(bang), TokenTree::Group(args)]
+            if mac == "include_str" && bang.as_char() == '!' =>
+        {
+            let rel = match args.stream().into_iter().next() {
+                Some(TokenTree::Literal(lit)) => str_literal_value(&lit),
+                _ => None,
+            };
+            let base = source_path.parent().unwrap_or(Path::new(""));
+            rel.and_then(|rel| fs::read_to_string(base.join(rel)).ok())
+        }
+        _ => None,
+    };
+    let Some(text) = text else {
+        eprintln!(
+            "Warnung: doc-Attribut in {}:{} nicht auswertbar",
+            source_path.display(),
+            line
+        );
+        return Vec::new();
+    };
+    // Bei Doc-Kommentaren deckt der Span des `#` den Kommentar selbst ab
+    let sugar = pound.span().source_text().unwrap_or_default();
+    if sugar.starts_with("//") {
+        let text = text.strip_prefix(' ').unwrap_or(&text).to_string();
+        vec![DocLine { line, text }]
+    } else if sugar.starts_with("/*") {
+        let lines = text
+            .split('\n')
+            .enumerate()
+            .map(|(k, l)| DocLine {
+                line: line + k,
+                text: l.trim_end_matches('\r').to_string(),
+            })
+            .collect();
+        strip_block_gutter(lines)
+    } else {
+        text.lines()
+            .map(|l| DocLine {
+                line,
+                text: l.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Wert eines (Raw-)String-Literals
+fn str_literal_value(lit: &proc_macro2::Literal) -> Option<String> {
+    match syn::Lit::new(lit.clone()) {
+        syn::Lit::Str(s) => Some(s.value()),
+        _ => None,
+    }
+}
+
+/// Bereinigt die Zeilen eines Block-Kommentars: leere erste/letzte Zeile weg und,
+/// falls alle Folgezeilen mit `*` beginnen, den Rand (` * `) samt einem Leerzeichen entfernen
+fn strip_block_gutter(mut lines: Vec<DocLine>) -> Vec<DocLine> {
+    if lines.last().is_some_and(|l| l.text.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return lines;
+    }
+    let mut rest = lines.split_off(1);
+    let mut first = lines;
+    // Leerzeilen ohne `*` unterbrechen den Rand nicht
+    let is_blank = |l: &DocLine| l.text.trim().is_empty();
+    if rest
+        .iter()
+        .all(|l| is_blank(l) || l.text.trim_start().starts_with('*'))
+    {
+        for l in rest.iter_mut() {
+            if is_blank(l) {
+                l.text.clear();
+                continue;
             }
-        })
+            let text = &l.text.trim_start()[1..];
+            l.text = text.strip_prefix(' ').unwrap_or(text).to_string();
+        }
+    }
+    // Text direkt hinter `/**` gehört zur ersten Zeile
+    if first[0].text.trim().is_empty() {
+        first.clear();
+    } else if let Some(text) = first[0].text.strip_prefix(' ') {
+        first[0].text = text.to_string();
+    }
+    first.extend(rest);
+    first
+}
+
+/// Entfernt führende/abschließende Leerzeilen und die gemeinsame Einrückung eines Blocks
+fn dedent_block(lines: &[&str]) -> String {
+    let is_blank = |l: &&str| l.trim().is_empty();
+    let start = lines
+        .iter()
+        .position(|l| !is_blank(l))
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|l| !is_blank(l))
+        .map_or(start, |e| e + 1);
+    let lines = &lines[start..end];
+    let indent = lines
+        .iter()
+        .filter(|l| !is_blank(l))
+        .map(|l| l.len() - l.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| if is_blank(l) { "" } else { &l[indent..] })
         .collect::<Vec<_>>()
         .join("\n")
 }
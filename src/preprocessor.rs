@@ -0,0 +1,124 @@
+use crate::config::SummaryConfig;
+use crate::parser;
+use crate::writer::{self, OutlineEntry};
+use anyhow::Result;
+use mdbook::book::{Book, BookItem, Chapter};
+use mdbook::preprocess::{CmdPreprocessor, Preprocessor, PreprocessorContext};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Quellordner, wenn in book.toml unter `[preprocessor.rustdocmd]` kein `source` steht
+/// (relativ zum Buch-Root)
+const DEFAULT_SOURCE: &str = "../src";
+
+/// mdBook-Präprozessor: setzt die Marker-Kapitel beim `mdbook build` direkt in
+/// das Buch ein, ohne .md-Dateien zu schreiben. Kapitel, die SUMMARY.md bereits
+/// nennt, bekommen ihren Inhalt aus dem Quelltext; alle anderen werden angehängt.
+pub struct RustdocmdPreprocessor;
+
+impl Preprocessor for RustdocmdPreprocessor {
+    fn name(&self) -> &str {
+        "rustdocmd"
+    }
+
+    fn run(&self, ctx: &PreprocessorContext, mut book: Book) -> mdbook::errors::Result<Book> {
+        let table = ctx.config.get_preprocessor(self.name());
+        let source = table
+            .and_then(|t| t.get("source"))
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_SOURCE);
+        let summary_config: SummaryConfig = match table.and_then(|t| t.get("summary")) {
+            Some(value) => value.clone().try_into()?,
+            None => SummaryConfig::default(),
+        };
+        let blocks = extract_blocks(&ctx.root.join(source))?;
+        let outline = writer::chapter_outline(&blocks, &summary_config)?;
+        inject_chapters(&mut book, &outline);
+        Ok(book)
+    }
+
+    fn supports_renderer(&self, _renderer: &str) -> bool {
+        true
+    }
+}
+
+/// Marker-Blöcke aller .rs-Dateien unter `source_dir`, in fester Reihenfolge.
+/// Eine nicht parsebare Datei bricht den Build ab, statt ihre Kapitel wegzulassen.
+fn extract_blocks(source_dir: &Path) -> Result<Vec<parser::MarkerBlock>> {
+    let mut blocks = Vec::new();
+    for entry in WalkDir::new(source_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.path().extension().map(|ext| ext == "rs") != Some(true) {
+            continue;
+        }
+        let content = fs::read_to_string(entry.path())?;
+        let docs = parser::extract_rustdoc_comments(&content, entry.path())?;
+        blocks.extend(parser::extract_marker_blocks(&docs));
+    }
+    Ok(blocks)
+}
+
+/// Sucht ein Kapitel über seinen Pfad relativ zu SUMMARY.md
+fn find_chapter_mut<'a>(items: &'a mut [BookItem], path: &Path) -> Option<&'a mut Chapter> {
+    for item in items {
+        if let BookItem::Chapter(chapter) = item {
+            if chapter.path.as_deref() == Some(path) {
+                return Some(chapter);
+            }
+            if let Some(found) = find_chapter_mut(&mut chapter.sub_items, path) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Ersetzt den Inhalt vorhandener Kapitel und hängt fehlende an, Unterkapitel
+/// unter ihrem Elternkapitel. Entwürfe werden nicht eingefügt.
+fn inject_chapters(book: &mut Book, outline: &[OutlineEntry]) {
+    // Vorfahren des aktuellen Eintrags: (Tiefe, Pfad, Titel)
+    let mut ancestors: Vec<(usize, PathBuf, String)> = Vec::new();
+    for entry in outline {
+        let path = PathBuf::from(&entry.target_md);
+        ancestors.retain(|(depth, _, _)| *depth < entry.depth);
+        let Some(content) = &entry.content else {
+            continue;
+        };
+        if let Some(chapter) = find_chapter_mut(&mut book.sections, &path) {
+            chapter.content = content.clone();
+        } else {
+            let parent_names = ancestors.iter().map(|(_, _, name)| name.clone()).collect();
+            let chapter = Chapter::new(&entry.title, content.clone(), &path, parent_names);
+            let parent = ancestors
+                .last()
+                .and_then(|(_, parent, _)| find_chapter_mut(&mut book.sections, parent));
+            match parent {
+                Some(parent) => parent.sub_items.push(BookItem::Chapter(chapter)),
+                None => {
+                    book.push_item(chapter);
+                }
+            }
+        }
+        ancestors.push((entry.depth, path, entry.title.clone()));
+    }
+}
+
+/// Präprozessor-Protokoll: Kontext und Buch als JSON von stdin, Ergebnis nach stdout
+pub fn handle_preprocessing() -> Result<()> {
+    let (ctx, book) = CmdPreprocessor::parse_input(io::stdin())?;
+    if ctx.mdbook_version != mdbook::MDBOOK_VERSION {
+        eprintln!(
+            "Warnung: rustdocmd wurde mit mdBook {} gebaut, aufgerufen von mdBook {}",
+            mdbook::MDBOOK_VERSION,
+            ctx.mdbook_version
+        );
+    }
+    let processed = RustdocmdPreprocessor.run(&ctx, book)?;
+    serde_json::to_writer(io::stdout(), &processed)?;
+    Ok(())
+}
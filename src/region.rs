@@ -0,0 +1,107 @@
+use regex::Regex;
+use std::ops::Range;
+
+/// Ein verwalteter Bereich `<!-- rustdocmd:begin name -->` ... `<!-- rustdocmd:end name -->`
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub name: String,        // leer bei `<!-- rustdocmd:begin -->`
+    pub inner: Range<usize>, // Bytebereich zwischen den beiden Markern
+}
+
+/// Beginn des unbenannten Bereichs (z.B. in SUMMARY.md)
+pub const REGION_BEGIN: &str = "<!-- rustdocmd:begin -->";
+/// Ende des unbenannten Bereichs
+pub const REGION_END: &str = "<!-- rustdocmd:end -->";
+
+/// Öffnender Marker eines Bereichs, optional mit Namen
+pub fn begin_marker(name: &str) -> String {
+    if name.is_empty() {
+        REGION_BEGIN.to_string()
+    } else {
+        format!("<!-- rustdocmd:begin {} -->", name)
+    }
+}
+
+/// Schließender Marker eines Bereichs, optional mit Namen
+pub fn end_marker(name: &str) -> String {
+    if name.is_empty() {
+        REGION_END.to_string()
+    } else {
+        format!("<!-- rustdocmd:end {} -->", name)
+    }
+}
+
+/// Findet alle Bereiche in `text`. Nicht geschlossene Bereiche werden
+/// gemeldet und ignoriert; `file` dient nur den Warnungen.
+pub fn find_regions(text: &str, file: &str) -> Vec<Region> {
+    let re_begin = Regex::new(r"<!--\s*rustdocmd:begin(?:\s+([\w\-.]+))?\s*-->").unwrap();
+    let mut regions = Vec::new();
+    let mut pos = 0;
+    while let Some(cap) = re_begin.captures_at(text, pos) {
+        let begin = cap.get(0).unwrap();
+        let name = cap.get(1).map_or("", |m| m.as_str()).to_string();
+        let mut start = begin.end();
+        if text[start..].starts_with("\r\n") {
+            start += 2;
+        } else if text[start..].starts_with('\n') {
+            start += 1;
+        }
+        let re_end = Regex::new(&format!(
+            r"<!--\s*rustdocmd:end{}\s*-->",
+            if name.is_empty() {
+                String::new()
+            } else {
+                format!(r"\s+{}", regex::escape(&name))
+            }
+        ))
+        .unwrap();
+        match re_end.find_at(text, start) {
+            Some(end) => {
+                regions.push(Region {
+                    name,
+                    inner: start..end.start(),
+                });
+                pos = end.end();
+            }
+            None => {
+                eprintln!(
+                    "Warnung: {} enthält {} ohne {}",
+                    file,
+                    begin.as_str(),
+                    end_marker(&name)
+                );
+                pos = begin.end();
+            }
+        }
+    }
+    regions
+}
+
+/// Ersetzt den Inhalt aller Bereiche durch `fill(name)`; Bereiche, für die
+/// `fill` nichts liefert, bleiben unverändert. Alles außerhalb der Bereiche
+/// bleibt Byte für Byte erhalten. Der neue Inhalt übernimmt das Zeilenende
+/// der Datei (`\r\n` oder `\n`).
+pub fn splice(
+    text: &str,
+    regions: &[Region],
+    mut fill: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for region in regions {
+        out.push_str(&text[pos..region.inner.start]);
+        match fill(&region.name) {
+            Some(content) if content.is_empty() => {}
+            Some(content) => {
+                let content = content.replace("\r\n", "\n");
+                out.push_str(&content.trim_end_matches('\n').replace('\n', newline));
+                out.push_str(newline);
+            }
+            None => out.push_str(&text[region.inner.clone()]),
+        }
+        pos = region.inner.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
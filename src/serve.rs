@@ -0,0 +1,220 @@
+use crate::config::BookLayout;
+use anyhow::{anyhow, Result};
+use notify::{RecursiveMode, Watcher};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// Pfad, unter dem die ausgelieferten Seiten den Stand des Buchs abfragen
+const VERSION_PATH: &str = "/__rustdocmd/version";
+
+/// Wartezeit nach einer Änderung, damit mehrere Speichervorgänge nur einen Build auslösen
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Wird in jede HTML-Seite eingefügt: lädt die Seite neu, sobald sich der Stand ändert
+const RELOAD_SCRIPT: &str = r#"<script>
+(function () {
+    var version = null;
+    setInterval(function () {
+        fetch("/__rustdocmd/version").then(function (r) { return r.text(); }).then(function (v) {
+            if (version !== null && v !== version) { location.reload(); }
+            version = v;
+        }).catch(function () {});
+    }, 1000);
+})();
+</script>"#;
+
+/// Liefert das gebaute Buch aus und baut es bei Änderungen neu: Änderungen an
+/// .rs-Dateien unter `source_dir` lösen zuerst `regenerate` aus, Änderungen an
+/// handgeschriebenen Kapiteln nur den Build. Läuft, bis der Prozess beendet wird.
+pub fn serve(
+    layout: &BookLayout,
+    source_dir: &Path,
+    hostname: &str,
+    port: u16,
+    mut regenerate: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let book = mdbook::MDBook::load(&layout.root)
+        .map_err(|e| anyhow!("mdBook in {} nicht ladbar: {:#}", layout.root.display(), e))?;
+    let build_dir = book.build_dir_for("html");
+    let version = Arc::new(AtomicUsize::new(0));
+
+    let listener = TcpListener::bind((hostname, port))?;
+    println!("Buch unter http://{}:{} (Strg+C beendet)", hostname, port);
+    {
+        let build_dir = build_dir.clone();
+        let version = Arc::clone(&version);
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(|s| s.ok()) {
+                let build_dir = build_dir.clone();
+                let version = Arc::clone(&version);
+                thread::spawn(move || {
+                    if let Err(e) = handle_request(stream, &build_dir, &version) {
+                        eprintln!("Warnung: Anfrage fehlgeschlagen: {}", e);
+                    }
+                });
+            }
+        });
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    let source_dir = fs::canonicalize(source_dir)?;
+    let target_dir = fs::canonicalize(&layout.target_dir)?;
+    watcher.watch(&source_dir, RecursiveMode::Recursive)?;
+    watcher.watch(&target_dir, RecursiveMode::Recursive)?;
+    let event_paths =
+        |event: notify::Result<notify::Event>| event.map(|e| e.paths).unwrap_or_default();
+    // Pfade, die während des letzten Builds geändert wurden
+    let mut pending: Vec<PathBuf> = Vec::new();
+    loop {
+        if pending.is_empty() {
+            match rx.recv() {
+                Ok(event) => pending.extend(event_paths(event)),
+                Err(_) => break,
+            }
+        }
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            pending.extend(event_paths(event));
+        }
+        let paths = std::mem::take(&mut pending);
+        let sources_changed = paths
+            .iter()
+            .any(|p| p.starts_with(&source_dir) && p.extension().is_some_and(|e| e == "rs"));
+        let chapters_changed = paths.iter().any(|p| !is_temporary(p));
+        if !sources_changed && !chapters_changed {
+            continue;
+        }
+        if sources_changed {
+            if let Err(e) = regenerate() {
+                eprintln!("Fehler: {:#}", e);
+                continue;
+            }
+        }
+        let rebuilt = mdbook::MDBook::load(&layout.root).and_then(|book| book.build());
+        match rebuilt {
+            Ok(()) => {
+                version.fetch_add(1, Ordering::SeqCst);
+                println!("mdBook neu gebaut");
+            }
+            Err(e) => eprintln!("Fehler: mdBook-Build fehlgeschlagen: {:#}", e),
+        }
+        // Ereignisse durch die eigenen Schreibvorgänge im Kapitelordner verwerfen;
+        // während des Builds gespeicherte Quelldateien lösen den nächsten Lauf aus
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            pending.extend(
+                event_paths(event)
+                    .into_iter()
+                    .filter(|p| !is_temporary(p) && !p.starts_with(&target_dir)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Temporäre Dateien und Sicherungen von `OutputPlan::apply`
+fn is_temporary(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().contains(".rustdocmd-"))
+}
+
+/// Beantwortet eine GET-Anfrage mit einer Datei aus dem Build-Ordner
+fn handle_request(mut stream: TcpStream, build_dir: &Path, version: &AtomicUsize) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let target = target.split(['?', '#']).next().unwrap_or("/");
+
+    if target == VERSION_PATH {
+        let body = version.load(Ordering::SeqCst).to_string();
+        return respond(&mut stream, "200 OK", "text/plain", body.as_bytes());
+    }
+    let Some(path) = resolve(build_dir, target) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"not found");
+    };
+    let mut body = fs::read(&path)?;
+    let content_type = content_type(&path);
+    if content_type.starts_with("text/html") {
+        let html = String::from_utf8_lossy(&body);
+        let html = match html.rfind("</body>") {
+            Some(i) => format!("{}{}{}", &html[..i], RELOAD_SCRIPT, &html[i..]),
+            None => format!("{}{}", html, RELOAD_SCRIPT),
+        };
+        body = html.into_bytes();
+    }
+    respond(&mut stream, "200 OK", content_type, &body)
+}
+
+/// Datei zu einem URL-Pfad (z.B. `/erste%20schritte.html`); Verzeichnisse liefern
+/// ihre index.html, Pfade mit `..` werden abgelehnt
+fn resolve(build_dir: &Path, target: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(target.trim_start_matches('/'))?;
+    let relative = Path::new(&decoded);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let mut path = build_dir.join(relative);
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    path.is_file().then_some(path)
+}
+
+/// Dekodiert `%XX`-Folgen eines URL-Pfads; `None` bei ungültigen Folgen oder
+/// wenn das Ergebnis kein UTF-8 ist
+fn percent_decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::region::{REGION_BEGIN, REGION_END};
+use regex::Regex;
+use std::collections::BTreeSet;
+
+/// Listeneintrag `* [Titel](./pfad.md)`; Gruppe 1 ist das Linkziel ohne `./`
+const ENTRY_PATTERN: &str = r"^\s*[-*]\s*\[.*\]\(\s*(?:\./)?([^)\s]*)\s*\)\s*$";
+
+/// Setzt die generierten Einträge in eine bestehende SUMMARY.md ein.
+///
+/// Gibt es bereits einen verwalteten Bereich, wird nur dieser ersetzt; alles
+/// außerhalb bleibt unverändert. Ohne Bereich (z.B. bei einer SUMMARY.md aus
+/// älteren Versionen) werden die Einträge, die auf eigene Kapitel (`owned`)
+/// verweisen, entfernt und der Bereich an der Stelle des ersten davon eingefügt.
+/// Eine neue SUMMARY.md bekommt `title` als Überschrift.
+pub fn merge(existing: Option<&str>, title: &str, entries: &str, owned: &[&str]) -> String {
+    let region = format!("{}\n{}{}\n", REGION_BEGIN, entries, REGION_END);
+    let Some(existing) = existing else {
+        return format!("# {}\n\n{}", title, region);
+    };
+    if let Err(e) = mdbook::book::parse_summary(existing) {
+        eprintln!("Warnung: bestehende SUMMARY.md ist ungültig: {}", e);
+    }
+
+    if let Some(begin) = existing.find(REGION_BEGIN) {
+        if let Some(end) = existing[begin..].find(REGION_END).map(|e| begin + e) {
+            let mut rest = &existing[end + REGION_END.len()..];
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+            return format!("{}{}{}", &existing[..begin], region, rest);
+        }
+        eprintln!(
+            "Warnung: SUMMARY.md enthält {} ohne {}",
+            REGION_BEGIN, REGION_END
+        );
+    }
+
+    let re_entry = Regex::new(ENTRY_PATTERN).unwrap();
+    let mut merged = String::new();
+    let mut inserted = false;
+    for line in existing.split_inclusive('\n') {
+        let is_owned = re_entry
+            .captures(line.trim_end())
+            .is_some_and(|cap| owned.contains(&&cap[1]));
+        if !is_owned {
+            merged.push_str(line);
+        } else if !inserted {
+            merged.push_str(&region);
+            inserted = true;
+        }
+    }
+    if !inserted {
+        if !merged.is_empty() && !merged.ends_with("\n\n") {
+            merged.push_str(if merged.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        merged.push_str(&region);
+    }
+    merged
+}
+
+/// Prüft, ob mdBook die SUMMARY.md lesen kann, und warnt andernfalls
+pub fn validate(summary: &str) {
+    if let Err(e) = mdbook::book::parse_summary(summary) {
+        eprintln!(
+            "Warnung: erzeugte SUMMARY.md ist für mdBook ungültig: {}",
+            e
+        );
+    }
+}
+
+/// Alle Linkziele der SUMMARY.md (Listeneinträge und Präfix-/Suffix-Kapitel)
+pub fn links(summary: &str) -> BTreeSet<String> {
+    let re_link = Regex::new(r"^\s*(?:[-*]\s*)?\[.*\]\(\s*(?:\./)?([^)\s]*)\s*\)\s*$").unwrap();
+    summary
+        .lines()
+        .filter_map(|line| re_link.captures(line))
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Wartezeit nach einer Änderung, damit mehrere Speichervorgänge nur einen Lauf auslösen
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Beobachtet `source_dir` und ruft `on_change` mit den betroffenen Pfaden auf,
+/// gesammelt bis zur nächsten Ruhepause. Gemeldet werden .rs-Dateien sowie
+/// angelegte, entfernte und umbenannte Pfade (z.B. verschobene Ordner), jeweils
+/// unterhalb von `source_dir` so angegeben wie `source_dir` selbst. Läuft, bis
+/// der Prozess beendet wird; Fehler von `on_change` werden nur ausgegeben.
+pub fn watch(
+    source_dir: &Path,
+    mut on_change: impl FnMut(&BTreeSet<PathBuf>) -> Result<()>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    let canonical = fs::canonicalize(source_dir)?;
+    watcher.watch(&canonical, RecursiveMode::Recursive)?;
+    println!("Beobachte {} (Strg+C beendet)", source_dir.display());
+
+    while let Ok(event) = rx.recv() {
+        let mut events = vec![event];
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            events.push(event);
+        }
+        let mut changed = BTreeSet::new();
+        for event in events {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    eprintln!("Warnung: Dateiüberwachung: {}", e);
+                    continue;
+                }
+            };
+            let structural = matches!(
+                event.kind,
+                EventKind::Create(_)
+                    | EventKind::Remove(_)
+                    | EventKind::Modify(ModifyKind::Name(_))
+            );
+            for path in event.paths {
+                let Ok(relative) = path.strip_prefix(&canonical) else {
+                    continue;
+                };
+                if structural || path.extension().is_some_and(|e| e == "rs") {
+                    changed.insert(source_dir.join(relative));
+                }
+            }
+        }
+        if changed.is_empty() {
+            continue;
+        }
+        if let Err(e) = on_change(&changed) {
+            eprintln!("Fehler: {:#}", e);
+        }
+    }
+    Ok(())
+}
@@ -1,39 +1,34 @@
-use crate::parser::ReadmeBlock;
-pub fn write_readme(
-    readme_blocks: &[ReadmeBlock],
-    readme_path: &Path,
-    dry_run: bool,
-) -> io::Result<()> {
-    let mut content = String::new();
-    for block in readme_blocks {
-        content.push_str(&block.content);
-        content.push_str("\n\n");
-    }
-    if dry_run {
-        println!(
-            "[dry-run] write {} ({} bytes)",
-            readme_path.display(),
-            content.len()
-        );
-    } else {
-        fs::write(readme_path, content.trim_end())?;
-    }
-    Ok(())
-}
-use crate::parser::MarkerBlock;
-use std::collections::BTreeMap;
+use crate::config::{BookLayout, DuplicateOrder, ReadmeConfig, SummaryConfig, TitleTransform};
+use crate::output::OutputPlan;
+use crate::parser::{ChapterOrder, InjectBlock, MarkerBlock};
+use crate::region;
+use crate::summary;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
 
-fn title_from_filename(filename: &str) -> String {
-    let stem = filename.trim_end_matches(".md");
-    stem.split(|c: char| c == '-' || c == '_' || c == ' ')
+fn title_from_filename(filename: &str, config: &SummaryConfig) -> String {
+    let name = filename.rsplit('/').next().unwrap_or(filename);
+    let stem = name.trim_end_matches(".md");
+    if config.title_transform == TitleTransform::FileName {
+        return stem.to_string();
+    }
+    stem.split(['-', '_', ' '])
         .filter(|s| !s.is_empty())
-        .map(|w| {
+        .enumerate()
+        .map(|(i, w)| {
+            if let Some(acronym) = config.acronyms.iter().find(|a| a.eq_ignore_ascii_case(w)) {
+                return acronym.clone();
+            }
             let mut chars = w.chars();
             match chars.next() {
-                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                Some(first) if i == 0 || config.title_transform == TitleTransform::TitleCase => {
+                    first.to_uppercase().collect::<String>() + chars.as_str()
+                }
+                Some(_) => w.to_string(),
                 None => String::new(),
             }
         })
@@ -41,98 +36,738 @@ fn title_from_filename(filename: &str) -> String {
         .join(" ")
 }
 
-pub fn write_markdown_and_summary(
-    blocks: &[MarkerBlock],
-    target_dir: &Path,
-    summary_path: &Path,
-    dry_run: bool,
-    mirror_root_summary: bool,
-) -> io::Result<()> {
-    // 1. Schreibe alle .md-Dateien
+/// Text der ersten Markdown-Überschrift (`# Titel` oder unterstrichen mit `===`/`---`),
+/// Codeblöcke werden übersprungen
+fn title_from_heading(content: &str) -> Option<String> {
+    let re_atx = Regex::new(r"^ {0,3}#{1,6}\s+(.*?)(?:\s+#+)?\s*$").unwrap();
+    let re_setext = Regex::new(r"^ {0,3}(?:=+|-+)\s*$").unwrap();
+    let mut in_code = false;
+    let mut previous: Option<&str> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            previous = None;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(cap) = re_atx.captures(line) {
+            return Some(cap[1].to_string()).filter(|t| !t.is_empty());
+        }
+        if let Some(text) = previous.filter(|_| re_setext.is_match(line)) {
+            return Some(text.trim().to_string());
+        }
+        previous = Some(line).filter(|l| !l.trim().is_empty());
+    }
+    None
+}
+
+/// Ein Kapitel: alle Blöcke mit demselben Ziel, sortiert nach Fragment-Index
+/// (`<file.md(1)#2>`), ohne Index nach Quelldatei und Zeile
+struct Chapter<'a> {
+    target_md: &'a str,
+    fragments: Vec<&'a MarkerBlock>,
+}
+
+impl Chapter<'_> {
+    /// Kleinste Ordnungsnummer der Fragmente
+    fn order(&self) -> Option<&ChapterOrder> {
+        self.fragments.iter().filter_map(|b| b.order.as_ref()).min()
+    }
+
+    fn content(&self) -> String {
+        self.fragments
+            .iter()
+            .map(|b| b.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Erster Wert eines Attributs über alle Fragmente
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.fragments.iter().find_map(|b| b.attribute(name))
+    }
+
+    /// Entwurfskapitel (`draft`) stehen ohne Datei in SUMMARY.md
+    fn is_draft(&self) -> bool {
+        self.fragments.iter().any(|b| b.has_attribute("draft"))
+    }
+
+    /// `after=`/`before=`-Bezüge aller Fragmente als (Attribut, Ziel, Block)
+    fn relations(&self) -> impl Iterator<Item = (&'static str, &str, &MarkerBlock)> {
+        self.fragments.iter().flat_map(|b| {
+            let after = b.attribute("after").map(|t| ("after", t, *b));
+            let before = b.attribute("before").map(|t| ("before", t, *b));
+            after.into_iter().chain(before)
+        })
+    }
+
+    /// Herkunft aller Fragmente, z.B. "src/a.rs:3-9, src/b.rs:1-4"
+    fn sources(&self) -> String {
+        self.fragments
+            .iter()
+            .map(|b| b.location.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Fasst Blöcke mit gleichem Ziel zu Kapiteln zusammen (Reihenfolge des ersten Auftretens)
+fn collect_chapters(blocks: &[MarkerBlock]) -> Vec<Chapter<'_>> {
+    let mut chapters: Vec<Chapter> = Vec::new();
     for block in blocks {
-        let md_path = target_dir.join(&block.target_md);
-        if dry_run {
-            println!(
-                "[dry-run] write {} ({} bytes)",
-                md_path.display(),
-                block.content.len()
+        match chapters.iter_mut().find(|c| c.target_md == block.target_md) {
+            Some(chapter) => chapter.fragments.push(block),
+            None => chapters.push(Chapter {
+                target_md: &block.target_md,
+                fragments: vec![block],
+            }),
+        }
+    }
+    for chapter in &mut chapters {
+        chapter.fragments.sort_by(|a, b| {
+            (
+                a.fragment.unwrap_or(usize::MAX),
+                &a.location.path,
+                a.location.start_line,
+            )
+                .cmp(&(
+                    b.fragment.unwrap_or(usize::MAX),
+                    &b.location.path,
+                    b.location.start_line,
+                ))
+        });
+        let mut orders: Vec<String> = chapter
+            .fragments
+            .iter()
+            .filter_map(|b| b.order.as_ref().map(ChapterOrder::to_string))
+            .collect();
+        orders.sort();
+        orders.dedup();
+        if let (true, Some(order)) = (orders.len() > 1, chapter.order()) {
+            eprintln!(
+                "Warnung: {} hat widersprüchliche Ordnungsnummern {} ({}), verwende {}",
+                chapter.target_md,
+                orders.join(", "),
+                chapter.sources(),
+                order
             );
-        } else {
-            fs::write(&md_path, &block.content)?;
         }
     }
+    chapters
+}
 
-    // 2. SUMMARY.md einlesen (oder neu anlegen)
-    let _summary = if summary_path.exists() {
-        fs::read_to_string(summary_path)?
-    } else {
-        String::from("# Summary\n\n")
-    };
+/// Sortierschlüssel in SUMMARY.md: nummerierte Kapitel zuerst, dann unnummerierte
+/// in der Reihenfolge ihres Auftretens
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SummaryKey {
+    Numbered(ChapterOrder),
+    Unnumbered(usize),
+}
 
-    // 3. Map für Reihenfolge und Einträge bauen
-    let mut entries: BTreeMap<usize, (&MarkerBlock, String)> = BTreeMap::new();
+/// Sortiert die Kapitel für SUMMARY.md. Gleiche Nummern werden nach Quelldatei,
+/// Zeile und Ziel aufgelöst und je nach `duplicate_order` gemeldet oder abgelehnt.
+fn summary_order<'a>(
+    chapters: &'a [Chapter<'a>],
+    duplicate_order: DuplicateOrder,
+) -> io::Result<Vec<(SummaryKey, &'a Chapter<'a>)>> {
     let mut last_index = 0;
-    for block in blocks {
-        let idx = block.order.unwrap_or_else(|| {
-            last_index += 1;
-            1000 + last_index // große Zahl = ans Ende
-        });
-        let title = title_from_filename(&block.target_md);
-        let entry = format!("* [{}]({})\n", title, block.target_md);
-        entries.insert(idx, (block, entry));
-    }
-
-    // 4. Neue SUMMARY.md bauen
-    let mut new_summary = String::from("# Summary\n\n");
-    for (_idx, (_block, entry)) in &entries {
-        new_summary.push_str(entry);
-    }
-    if dry_run {
-        let entries_count = entries.len();
-        println!(
-            "[dry-run] write {} with {} entries",
-            summary_path.display(),
-            entries_count
-        );
-    } else {
-        // Debug: Zeige, was in SUMMARY.md geschrieben wird
-        eprintln!(
-            "[debug] writing {} with content:\n{}",
-            summary_path.display(),
-            new_summary
+    let mut entries: Vec<(SummaryKey, &Chapter)> = chapters
+        .iter()
+        .map(|chapter| {
+            let key = match chapter.order() {
+                Some(order) => SummaryKey::Numbered(order.clone()),
+                None => {
+                    last_index += 1;
+                    SummaryKey::Unnumbered(last_index)
+                }
+            };
+            (key, chapter)
+        })
+        .collect();
+    entries.sort_by(|(ka, a), (kb, b)| {
+        let first = |c: &Chapter| {
+            let loc = &c.fragments[0].location;
+            (loc.path.clone(), loc.start_line)
+        };
+        (ka, first(a), a.target_md).cmp(&(kb, first(b), b.target_md))
+    });
+    for pair in entries.windows(2) {
+        let ((ka, a), (kb, b)) = (&pair[0], &pair[1]);
+        let SummaryKey::Numbered(order) = ka else {
+            continue;
+        };
+        if ka != kb {
+            continue;
+        }
+        let message = format!(
+            "{} ({}) und {} ({}) haben dieselbe Nummer {}",
+            a.target_md,
+            a.sources(),
+            b.target_md,
+            b.sources(),
+            order
         );
-        fs::write(summary_path, new_summary)?;
-        // Optional: Auch die SUMMARY.md im mdBook-Root aktualisieren (Kompatibilität)
-        if mirror_root_summary {
-            if let Some(root_dir) = summary_path.parent().and_then(|p| p.parent()) {
-                let root_summary = root_dir.join("SUMMARY.md");
-                eprintln!("[debug] mirroring summary to {}", root_summary.display());
-                let _ = fs::write(root_summary, fs::read_to_string(summary_path)?);
+        match duplicate_order {
+            DuplicateOrder::Error => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            DuplicateOrder::Warn => {
+                eprintln!("Warnung: {}; sortiert nach Quelldatei", message)
             }
+            DuplicateOrder::Path => {}
         }
     }
+    apply_relative_order(entries)
+}
 
-    // 5. Entferne .md-Dateien und Einträge, die nicht mehr in blocks vorkommen
-    let existing_files = match fs::read_dir(target_dir) {
-        Ok(rd) => rd
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map(|ext| ext == "md").unwrap_or(false))
-            .map(|e| e.path())
-            .collect::<Vec<_>>(),
-        Err(_) => Vec::new(),
-    };
-    let valid_files: Vec<PathBuf> = blocks
+/// Löst `after=`/`before=` per topologischer Sortierung auf. Unter den jeweils freien
+/// Kapiteln wird das mit der kleinsten Position gewählt, sodass die Nummern-Reihenfolge
+/// erhalten bleibt, soweit die Bezüge es zulassen. Zyklen und Bezüge auf unbekannte
+/// Kapitel sind Fehler.
+fn apply_relative_order<'a>(
+    entries: Vec<(SummaryKey, &'a Chapter<'a>)>,
+) -> io::Result<Vec<(SummaryKey, &'a Chapter<'a>)>> {
+    let index: HashMap<&str, usize> = entries
         .iter()
-        .map(|b| target_dir.join(&b.target_md))
+        .enumerate()
+        .map(|(i, (_, c))| (c.target_md, i))
         .collect();
-    for file in existing_files {
-        if !valid_files.contains(&file) {
-            if dry_run {
-                println!("[dry-run] remove {}", file.display());
+    // preds[i]: Kapitel, die vor Kapitel i stehen müssen
+    let mut preds: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); entries.len()];
+    // anchors[i]: erster Bezug eines unnummerierten Kapitels (Ziel, true = after)
+    let mut anchors: Vec<Option<(usize, bool)>> = vec![None; entries.len()];
+    for (i, (_, chapter)) in entries.iter().enumerate() {
+        for (kind, target, block) in chapter.relations() {
+            let j = *index.get(target).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}: {}=\"{}\" verweist auf kein Kapitel ({})",
+                        chapter.target_md, kind, target, block.location
+                    ),
+                )
+            })?;
+            let after = kind == "after";
+            if after {
+                preds[i].insert(j);
             } else {
-                let _ = fs::remove_file(&file);
+                preds[j].insert(i);
+            }
+            if chapter.order().is_none() && anchors[i].is_none() {
+                anchors[i] = Some((j, after));
+            }
+        }
+    }
+    let mut positions: Vec<Option<Vec<usize>>> = vec![None; entries.len()];
+    for i in 0..entries.len() {
+        relative_position(i, &anchors, &mut positions, &mut vec![false; entries.len()]);
+    }
+
+    let mut done = vec![false; entries.len()];
+    let mut sorted = Vec::with_capacity(entries.len());
+    while sorted.len() < entries.len() {
+        let next = (0..entries.len())
+            .filter(|&i| !done[i] && preds[i].iter().all(|&j| done[j]))
+            .min_by_key(|&i| positions[i].clone());
+        match next {
+            Some(i) => {
+                done[i] = true;
+                sorted.push(i);
+            }
+            None => {
+                let cycle: Vec<&str> = (0..entries.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| entries[i].1.target_md)
+                    .collect();
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Zyklus in after/before zwischen {}", cycle.join(", ")),
+                ));
+            }
+        }
+    }
+    let mut entries: Vec<Option<(SummaryKey, &Chapter)>> = entries.into_iter().map(Some).collect();
+    Ok(sorted
+        .into_iter()
+        .filter_map(|i| entries[i].take())
+        .collect())
+}
+
+/// Sortierposition eines Kapitels: nummerierte Kapitel behalten ihren Platz,
+/// unnummerierte mit Bezug landen direkt hinter (`after`) bzw. vor (`before`) ihrem Ziel
+fn relative_position(
+    i: usize,
+    anchors: &[Option<(usize, bool)>],
+    positions: &mut [Option<Vec<usize>>],
+    visiting: &mut [bool],
+) -> Vec<usize> {
+    if let Some(position) = &positions[i] {
+        return position.clone();
+    }
+    let base = vec![2 * i + 2];
+    let position = match anchors[i] {
+        Some((j, after)) if !visiting[i] => {
+            visiting[i] = true;
+            let mut position = relative_position(j, anchors, positions, visiting);
+            visiting[i] = false;
+            if !after {
+                *position.last_mut().unwrap() -= 1;
+            }
+            position.push(2);
+            position
+        }
+        _ => base,
+    };
+    positions[i] = Some(position.clone());
+    position
+}
+
+/// Pfad eines Kapitels im Zielverzeichnis; Ziele, die das Zielverzeichnis
+/// verlassen würden (absolut oder mit `..`), werden abgelehnt.
+fn chapter_path(target_dir: &Path, chapter: &Chapter) -> io::Result<PathBuf> {
+    let rel = Path::new(chapter.target_md);
+    if !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Ziel '{}' liegt außerhalb von {} ({})",
+                chapter.target_md,
+                target_dir.display(),
+                chapter.sources()
+            ),
+        ));
+    }
+    Ok(target_dir.join(rel))
+}
+
+/// Name des Manifests im Buch-Root (neben book.toml), das die erzeugten Kapitel
+/// auflistet; außerhalb des Zielverzeichnisses, damit mdBook es nicht ins Buch kopiert
+pub const MANIFEST_NAME: &str = ".rustdocmd-manifest";
+
+/// Liest die im letzten Lauf erzeugten Kapitel (relativ zum Zielverzeichnis).
+/// Einträge, die das Zielverzeichnis verlassen würden, werden ignoriert.
+fn read_manifest(manifest_path: &Path) -> io::Result<BTreeSet<String>> {
+    let content = match fs::read_to_string(manifest_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter(|l| {
+            Path::new(l)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        })
+        .map(str::to_string)
+        .collect())
+}
+
+fn manifest_content(files: &BTreeSet<String>) -> String {
+    let mut content =
+        String::from("# Von rustdocmd erzeugte Dateien (nur diese werden gelöscht)\n");
+    for file in files {
+        content.push_str(file);
+        content.push('\n');
+    }
+    content
+}
+
+/// Pfad relativ zum Zielverzeichnis mit `/` als Trenner, wie in Markern und im Manifest
+fn relative_name(path: &Path, target_dir: &Path) -> String {
+    let rel = path.strip_prefix(target_dir).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Einrückungstiefe eines Kapitels: eine Ebene unter dem nächsten vorhandenen
+/// Vorfahren, damit Lücken (2.1.3 ohne 2.1) kein ungültiges SUMMARY.md ergeben
+fn nested_depth(order: &ChapterOrder, depths: &BTreeMap<&ChapterOrder, usize>) -> usize {
+    let mut parent = order.parent();
+    while let Some(p) = parent {
+        if let Some(depth) = depths.get(&p) {
+            return depth + 1;
+        }
+        parent = p.parent();
+    }
+    0
+}
+
+/// Ein Eintrag des Inhaltsverzeichnisses: Kapitel mit Titel und Einrückungstiefe
+#[derive(Debug, Clone)]
+pub struct OutlineEntry {
+    pub target_md: String,
+    pub title: String,
+    pub depth: usize,
+    /// Inhalt des Kapitels; `None` bei Entwürfen (`draft`)
+    pub content: Option<String>,
+}
+
+/// Kapitel in der Reihenfolge des Inhaltsverzeichnisses (siehe `summary_order`)
+/// mit Titel (Attribut, Überschrift oder Dateiname) und Einrückungstiefe
+fn outline<'a>(
+    chapters: &'a [Chapter<'a>],
+    summary_config: &SummaryConfig,
+) -> io::Result<Vec<(usize, String, &'a Chapter<'a>)>> {
+    let ordered = summary_order(chapters, summary_config.duplicate_order)?;
+    let by_target: HashMap<&str, &Chapter> = chapters.iter().map(|c| (c.target_md, c)).collect();
+    let mut depths: BTreeMap<&ChapterOrder, usize> = BTreeMap::new();
+    let mut target_depths: HashMap<&str, usize> = HashMap::new();
+    let mut outline = Vec::with_capacity(ordered.len());
+    for (key, chapter) in &ordered {
+        let title = chapter
+            .attribute("title")
+            .map(str::to_string)
+            .or_else(|| title_from_heading(&chapter.content()))
+            .unwrap_or_else(|| title_from_filename(chapter.target_md, summary_config));
+        let depth = match key {
+            SummaryKey::Numbered(order) => {
+                let depth = nested_depth(order, &depths);
+                if depth < order.depth() {
+                    eprintln!(
+                        "Warnung: Lücke in der Kapitelhierarchie: {} ({}) hat kein Kapitel {}",
+                        order,
+                        chapter.target_md,
+                        order.parent().unwrap()
+                    );
+                }
+                depths.insert(order, depth);
+                depth
+            }
+            // mit after=/before= auf der Ebene des Bezugs (siehe `apply_relative_order`)
+            SummaryKey::Unnumbered(_) => match chapter.relations().next() {
+                Some((_, target, _)) => match target_depths.get(target) {
+                    Some(depth) => *depth,
+                    None => by_target
+                        .get(target)
+                        .and_then(|c| c.order())
+                        .map_or(0, |order| nested_depth(order, &depths)),
+                },
+                None => 0,
+            },
+        };
+        target_depths.insert(chapter.target_md, depth);
+        outline.push((depth, title, *chapter));
+    }
+    for pair in outline.windows(2) {
+        let ((depth, _, chapter), (next_depth, _, next)) = (&pair[0], &pair[1]);
+        if let (None, Some(order)) = (chapter.order(), next.order()) {
+            if next_depth > depth {
+                eprintln!(
+                    "Warnung: {} ({}) steht durch after/before vor {} ({}), das damit unter {} statt unter Kapitel {} eingerückt wird",
+                    chapter.target_md,
+                    chapter.sources(),
+                    next.target_md,
+                    order,
+                    chapter.target_md,
+                    order.parent().map_or_else(String::new, |p| p.to_string())
+                );
+            }
+        }
+    }
+    Ok(outline)
+}
+
+/// Inhaltsverzeichnis aller Kapitel ohne Dateien zu schreiben, z.B. für den
+/// mdBook-Präprozessor
+pub fn chapter_outline(
+    blocks: &[MarkerBlock],
+    summary_config: &SummaryConfig,
+) -> io::Result<Vec<OutlineEntry>> {
+    let chapters = collect_chapters(blocks);
+    for chapter in &chapters {
+        chapter_path(Path::new(""), chapter)?;
+    }
+    Ok(outline(&chapters, summary_config)?
+        .into_iter()
+        .map(|(depth, title, chapter)| OutlineEntry {
+            target_md: chapter.target_md.to_string(),
+            title,
+            depth,
+            content: (!chapter.is_draft()).then(|| chapter.content()),
+        })
+        .collect())
+}
+
+/// Plant Kapitel, SUMMARY.md, Manifest und das Löschen entfallener Kapitel;
+/// geschrieben wird erst beim Anwenden des Plans
+pub fn write_markdown_and_summary(
+    blocks: &[MarkerBlock],
+    layout: &BookLayout,
+    summary_config: &SummaryConfig,
+    plan: &mut OutputPlan,
+) -> io::Result<()> {
+    let target_dir = layout.target_dir.as_path();
+    let summary_path = layout.summary_path();
+    let summary_path = summary_path.as_path();
+    // Ziele und Reihenfolge vorab prüfen, damit bei Fehlern nichts geschrieben wird
+    let chapters = collect_chapters(blocks);
+    let md_paths = chapters
+        .iter()
+        .map(|c| chapter_path(target_dir, c))
+        .collect::<io::Result<Vec<_>>>()?;
+    let outline = outline(&chapters, summary_config)?;
+
+    // Entwurfskapitel bekommen keine Datei
+    let md_paths: Vec<(&Chapter, PathBuf)> = chapters
+        .iter()
+        .zip(md_paths)
+        .filter(|(chapter, _)| !chapter.is_draft())
+        .collect();
+    let manifest_path = layout.root.join(MANIFEST_NAME);
+    let mut previous = read_manifest(&manifest_path)?;
+    // Frühere Versionen legten das Manifest im Zielverzeichnis ab
+    let legacy_manifest = target_dir.join(MANIFEST_NAME);
+    if legacy_manifest != manifest_path && legacy_manifest.is_file() {
+        previous.extend(read_manifest(&legacy_manifest)?);
+        plan.remove(&legacy_manifest, target_dir);
+    }
+    let generated: BTreeSet<String> = md_paths
+        .iter()
+        .map(|(chapter, _)| chapter.target_md.to_string())
+        .collect();
+
+    // 1. Alle .md-Dateien schreiben
+    for (chapter, md_path) in &md_paths {
+        let content = chapter.content();
+        if md_path.exists() && !previous.contains(chapter.target_md) {
+            eprintln!(
+                "Warnung: {} wurde nicht von rustdocmd erzeugt und wird überschrieben ({})",
+                md_path.display(),
+                chapter.sources()
+            );
+        }
+        plan.write(md_path, content, chapter.sources());
+    }
+
+    // 2. SUMMARY.md einlesen (handgeschriebene Einträge bleiben erhalten)
+    let existing_summary = if summary_path.exists() {
+        Some(fs::read_to_string(summary_path)?)
+    } else {
+        None
+    };
+
+    // 3. Verwalteten Bereich der SUMMARY.md bauen, Unterkapitel eingerückt unter
+    //    ihrem Elternkapitel, und in die bestehende Datei einsetzen
+    let mut managed = String::new();
+    for (depth, title, chapter) in &outline {
+        let link = if chapter.is_draft() {
+            ""
+        } else {
+            chapter.target_md
+        };
+        managed.push_str(&"    ".repeat(*depth));
+        managed.push_str(&format!("* [{}]({})\n", title, link));
+    }
+    let owned: Vec<&str> = chapters.iter().map(|c| c.target_md).collect();
+    let title = layout.title.as_deref().unwrap_or("Summary");
+    let new_summary = summary::merge(existing_summary.as_deref(), title, &managed, &owned);
+    summary::validate(&new_summary);
+    let linked = summary::links(&new_summary);
+    plan.write(summary_path, new_summary.clone(), String::new());
+    // Optional: Auch eine Kopie der SUMMARY.md aktualisieren (Kompatibilität)
+    if let Some(mirror) = &layout.mirror_summary {
+        plan.write(mirror, new_summary, String::new());
+    }
+
+    // 4. Entferne nur früher erzeugte Kapitel (laut Manifest), die nicht mehr vorkommen
+    for rel in previous.difference(&generated) {
+        let file = target_dir.join(rel);
+        if !file.is_file() {
+            continue;
+        }
+        plan.remove(&file, target_dir);
+    }
+    // Fremde .md-Dateien bleiben erhalten; Warnung, wenn SUMMARY.md sie nicht kennt
+    for entry in WalkDir::new(target_dir).into_iter().filter_map(|e| e.ok()) {
+        let file = entry.path();
+        let is_md = file.extension().map(|ext| ext == "md").unwrap_or(false);
+        if !entry.file_type().is_file() || !is_md || file == summary_path {
+            continue;
+        }
+        let rel = relative_name(file, target_dir);
+        if !generated.contains(&rel) && !previous.contains(&rel) && !linked.contains(&rel) {
+            eprintln!(
+                "Warnung: {} wurde nicht von rustdocmd erzeugt und bleibt erhalten",
+                file.display()
+            );
+        }
+    }
+    plan.write(&manifest_path, manifest_content(&generated), String::new());
+    Ok(())
+}
+
+/// Name des Bereichs, in den das Inhaltsverzeichnis der Abschnitte geschrieben wird
+pub const TOC_REGION: &str = "toc";
+
+/// Anker einer Überschrift wie bei GitHub: Kleinbuchstaben, Leerzeichen als `-`,
+/// ohne Satzzeichen
+fn heading_anchor(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Inhalte eines Abschnitts (ohne Titel: Blöcke ohne `section`)
+type SectionParts<'a> = (Option<&'a str>, Vec<&'a str>);
+
+/// Setzt die Blöcke je Bereich zusammen: sortiert nach Ordnungsnummer, dann
+/// Quelldatei und Zeile; Blöcke mit `section` werden unter einer eigenen
+/// Überschrift gruppiert. Liefert die Bereiche und die Abschnittstitel.
+fn assemble_regions<'a>(
+    blocks: &[&'a InjectBlock],
+    config: &ReadmeConfig,
+) -> (Vec<(&'a str, String)>, Vec<&'a str>) {
+    let mut sorted = blocks.to_vec();
+    sorted.sort_by(|a, b| {
+        (
+            a.order.is_none(),
+            &a.order,
+            &a.location.path,
+            a.location.start_line,
+        )
+            .cmp(&(
+                b.order.is_none(),
+                &b.order,
+                &b.location.path,
+                b.location.start_line,
+            ))
+    });
+    let mut regions: Vec<(&str, Vec<SectionParts>)> = Vec::new();
+    for block in sorted {
+        let groups = match regions.iter_mut().find(|(name, _)| *name == block.region) {
+            Some((_, groups)) => groups,
+            None => {
+                regions.push((&block.region, Vec::new()));
+                &mut regions.last_mut().unwrap().1
             }
+        };
+        let section = block.section.as_deref();
+        match groups.iter_mut().find(|(s, _)| *s == section) {
+            Some((_, parts)) => parts.push(&block.content),
+            None => groups.push((section, vec![&block.content])),
         }
     }
+    let mut titles = Vec::new();
+    let assembled = regions
+        .into_iter()
+        .map(|(name, groups)| {
+            let content = groups
+                .into_iter()
+                .map(|(section, parts)| {
+                    let body = parts.join(&config.separator);
+                    match section {
+                        Some(title) => {
+                            titles.push(title);
+                            format!("## {}\n\n{}", title, body)
+                        }
+                        None => body,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(&config.separator);
+            (name, content)
+        })
+        .collect();
+    (assembled, titles)
+}
+
+/// Fügt Blöcke in die Bereiche `<!-- rustdocmd:begin name -->` ...
+/// `<!-- rustdocmd:end name -->` einer Datei ein (siehe `assemble_regions`).
+/// Alles außerhalb der Bereiche bleibt unverändert. Fehlt die Datei, wird sie
+/// nur mit `create_missing` (README.md) mit einem Bereich pro Name angelegt.
+pub fn write_injections(
+    blocks: &[&InjectBlock],
+    path: &Path,
+    create_missing: bool,
+    config: &ReadmeConfig,
+    plan: &mut OutputPlan,
+) -> io::Result<()> {
+    let (mut sections, titles) = assemble_regions(blocks, config);
+    if config.toc {
+        if sections.iter().any(|(name, _)| *name == TOC_REGION) {
+            eprintln!(
+                "Warnung: Bereich '{}' ist für das Inhaltsverzeichnis reserviert",
+                TOC_REGION
+            );
+            sections.retain(|(name, _)| *name != TOC_REGION);
+        }
+        let toc = titles
+            .iter()
+            .map(|title| format!("- [{}](#{})", title, heading_anchor(title)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        sections.insert(0, (TOC_REGION, toc));
+    }
+
+    let file = path.display().to_string();
+    let content = match fs::read_to_string(path) {
+        Ok(existing) => {
+            let regions = region::find_regions(&existing, &file);
+            if regions.is_empty() {
+                eprintln!(
+                    "Warnung: {} enthält keine Bereiche {} ... {}, wird nicht verändert",
+                    file,
+                    region::begin_marker("name"),
+                    region::end_marker("name")
+                );
+                return Ok(());
+            }
+            for (name, _) in &sections {
+                if !regions.iter().any(|r| r.name == *name) {
+                    eprintln!(
+                        "Warnung: {} enthält keinen Bereich {}, Blöcke werden ignoriert",
+                        file,
+                        region::begin_marker(name)
+                    );
+                }
+            }
+            region::splice(&existing, &regions, |name| {
+                let section = sections.iter().find(|(n, _)| *n == name);
+                if section.is_none() {
+                    eprintln!(
+                        "Warnung: kein Block für Bereich {} in {}",
+                        region::begin_marker(name),
+                        file
+                    );
+                }
+                Some(section.map(|(_, c)| c.clone()).unwrap_or_default())
+            })
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !create_missing => {
+            eprintln!("Warnung: {} existiert nicht, Blöcke werden ignoriert", file);
+            return Ok(());
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => sections
+            .iter()
+            .map(|(name, content)| {
+                format!(
+                    "{}\n{}\n{}\n",
+                    region::begin_marker(name),
+                    content,
+                    region::end_marker(name)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => return Err(e),
+    };
+    let sources = blocks
+        .iter()
+        .map(|b| b.location.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    plan.write(path, content, sources);
     Ok(())
 }
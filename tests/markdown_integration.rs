@@ -1,9 +1,29 @@
 use assert_cmd::prelude::*;
-use predicates::prelude::*;
+use predicates::prelude::PredicateBooleanExt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Command;
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
+
+/// Creates a temp project with `src/`, `mdbook/src/` and a default rustdocmd.toml
+fn project() -> std::io::Result<TempDir> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    fs::create_dir_all(root.join("src"))?;
+    fs::create_dir_all(root.join("mdbook").join("src"))?;
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n",
+    )?;
+    Ok(dir)
+}
+
+/// The rustdocmd binary, run from `root`
+fn rustdocmd(root: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("rustdocmd"));
+    cmd.current_dir(root);
+    cmd
+}
 
 #[test]
 fn generates_markdown_and_summary() -> Result<(), Box<dyn std::error::Error>> {
@@ -34,8 +54,8 @@ fn generates_markdown_and_summary() -> Result<(), Box<dyn std::error::Error>> {
     fs::write(root.join("rustdocmd.toml"), toml)?;
 
     // run binary in temp root
-    let mut cmd = Command::cargo_bin("rustdocmd")?;
-    cmd.current_dir(&root);
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("rustdocmd"));
+    cmd.current_dir(root);
     cmd.assert().success();
 
     // assert markdown file exists and contains content
@@ -46,7 +66,1398 @@ fn generates_markdown_and_summary() -> Result<(), Box<dyn std::error::Error>> {
     // assert SUMMARY.md exists and has entry
     let summary_path = mdbook_src.parent().unwrap().join("SUMMARY.md");
     let summary = fs::read_to_string(&summary_path)?;
-    assert!(summary.contains("* [intro](intro.md)"));
+    assert!(summary.contains("* [Hello Integration](intro.md)"));
+
+    Ok(())
+}
+
+#[test]
+fn keeps_indentation_inside_marker_blocks() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    // marker block indented as a whole, with a nested list and a fenced block in a list item
+    let sample_rs = r#"
+    //!   <nested.md(1)>
+    //!   1. Step one:
+    //!      ```toml
+    //!      [paths]
+    //!        source = "./src"
+    //!      ```
+    //!      - sub item
+    //!   </nested.md>
+    "#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+
+    rustdocmd(root).assert().success();
+
+    let md = fs::read_to_string(mdbook_src.join("nested.md"))?;
+    assert_eq!(
+        md,
+        "1. Step one:\n   ```toml\n   [paths]\n     source = \"./src\"\n   ```\n   - sub item"
+    );
+    Ok(())
+}
+
+#[test]
+fn finds_markers_in_block_doc_comments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let sample_rs = r#"
+/*!
+ * <module.md(1)>
+ * # Module docs
+ *
+ * - nested
+ *   - item
+ * </module.md>
+ */
+
+/**
+    <item.md(2)>
+    Item docs without gutter
+    </item.md>
+*/
+pub fn item() {}
+
+/**
+ * <gut.md(3)>
+ * Gutter with a gap
+
+ * after the gap
+ * </gut.md>
+ */
+pub fn gut() {}
+
+/*** not a doc comment <ignored.md> </ignored.md> */
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+
+    rustdocmd(root).assert().success();
+
+    let module = fs::read_to_string(mdbook_src.join("module.md"))?;
+    assert_eq!(module, "# Module docs\n\n- nested\n  - item");
+    let item = fs::read_to_string(mdbook_src.join("item.md"))?;
+    assert_eq!(item, "Item docs without gutter");
+    let gut = fs::read_to_string(mdbook_src.join("gut.md"))?;
+    assert_eq!(gut, "Gutter with a gap\n\nafter the gap");
+    assert!(!mdbook_src.join("ignored.md").exists());
+    Ok(())
+}
+
+#[test]
+fn finds_markers_in_doc_attributes_and_included_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::create_dir_all(src_dir.join("docs"))?;
+
+    let sample_rs = r##"
+#![doc = "<attr.md(1)>"]
+#![doc = "# From attributes\n\nSecond \"line\""]
+#![doc = r#"</attr.md>"#]
+
+#[doc = include_str!("docs/guide.md")]
+pub struct Guide;
+"##;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+    fs::write(
+        src_dir.join("docs").join("guide.md"),
+        "<guide.md(2)>\n# Included guide\n</guide.md>\n",
+    )?;
+
+    rustdocmd(root).assert().success();
+
+    let attr = fs::read_to_string(mdbook_src.join("attr.md"))?;
+    assert_eq!(attr, "# From attributes\n\nSecond \"line\"");
+    let guide = fs::read_to_string(mdbook_src.join("guide.md"))?;
+    assert_eq!(guide, "# Included guide");
+    Ok(())
+}
+
+#[test]
+fn ignores_marker_text_outside_doc_comments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let sample_rs = r####"
+/// <real.md(1)>
+/// Real chapter
+/// </real.md>
+pub fn documented() {
+    let s = "/// <string.md>\n/// </string.md>";
+    let r = r#"
+/// <raw.md>
+/// </raw.md>
+"#;
+    // /// <comment.md>
+    // /// </comment.md>
+}
+
+/// <split.md>
+pub struct First;
+/// </split.md>
+pub struct Second;
+
+/// <div align="center">
+/// Centered
+/// </div>
+/// <details open>
+/// <summary>More</summary>
+/// </details>
+pub struct Html;
+"####;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Block 1 (fn documented, ./src/sample.rs:2-4)",
+        ))
+        .stderr(predicates::str::contains("unbekanntes Attribut").not());
+
+    assert_eq!(
+        fs::read_to_string(mdbook_src.join("real.md"))?,
+        "Real chapter"
+    );
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert!(
+        !summary.contains("(div)") && !summary.contains("(details)"),
+        "{}",
+        summary
+    );
+    for ignored in [
+        "string.md",
+        "raw.md",
+        "comment.md",
+        "split.md",
+        "div",
+        "details",
+    ] {
+        assert!(
+            !mdbook_src.join(ignored).exists(),
+            "{} was extracted",
+            ignored
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn assigns_blocks_to_generic_items() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+
+    let sample_rs = r#"/// <outer.md(1)>
+/// Outer
+/// </outer.md>
+impl<T, U> Foo for Bar<T, U> {
+    //! <inner.md(2)>
+    //! Inner
+    //! </inner.md>
+}
+
+/// <convert.md(3)>
+/// Convert
+/// </convert.md>
+pub fn convert<T, U>(t: T) -> U
+where
+    T: Into<U>,
+    U: Clone,
+{
+    t.into()
+}
+
+/// <after.md(4)>
+/// After
+/// </after.md>
+pub struct After<F: Fn(u8) -> u8, G>(F, G);
+
+pub enum Flags {
+    /// <flag-a.md(5)>
+    /// A
+    /// </flag-a.md>
+    A = 1 << 2,
+    /// <flag-b.md(6)>
+    /// B
+    /// </flag-b.md>
+    B = 1 << 3,
+}
+"#;
+    fs::write(root.join("src").join("sample.rs"), sample_rs)?;
+
+    let mut assert = rustdocmd(root).assert().success();
+    for expected in [
+        "Block 1 (impl Bar, ./src/sample.rs:1-3)",
+        "Block 2 (impl Bar, ./src/sample.rs:5-7)",
+        "Block 3 (fn convert, ./src/sample.rs:10-12)",
+        "Block 4 (struct After, ./src/sample.rs:21-23)",
+        "Block 5 (A, ./src/sample.rs:27-29)",
+        "Block 6 (B, ./src/sample.rs:31-33)",
+    ] {
+        assert = assert.stdout(predicates::str::contains(expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn dry_run_points_back_to_source_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+
+    let sample_rs = r#"
+pub struct Before;
+
+/// Intro text
+/// <intro.md(1)>
+/// # Intro
+/// </intro.md>
+/// <readme>
+/// Readme text
+/// </readme>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+
+    rustdocmd(root)
+        .args(["--dry-run", "--generate-readme"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("<- ./src/sample.rs:5-7"))
+        .stdout(predicates::str::contains("<- ./src/sample.rs:8-10"));
+    assert!(!root.join("mdbook").join("src").join("intro.md").exists());
+    assert!(!root.join("README.md").exists());
+    Ok(())
+}
+
+#[test]
+fn writes_chapters_into_subdirectories() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let nested_rs = r#"
+/// <guide/setup/first-steps.md(1)>
+/// # First steps
+/// </guide/setup/first-steps.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), nested_rs)?;
+    rustdocmd(root).assert().success();
+    let chapter = mdbook_src
+        .join("guide")
+        .join("setup")
+        .join("first-steps.md");
+    assert_eq!(fs::read_to_string(&chapter)?, "# First steps");
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert!(summary.contains("* [First steps](guide/setup/first-steps.md)"));
+
+    // block removed: chapter and its now empty folders disappear
+    fs::write(src_dir.join("sample.rs"), "pub fn documented() {}\n")?;
+    rustdocmd(root).assert().success();
+    assert!(!mdbook_src.join("guide").exists());
+    assert!(mdbook_src.join("SUMMARY.md").exists());
+
+    // targets escaping the target directory are refused
+    let escaping_rs = r#"
+/// <../escape.md(1)>
+/// nope
+/// </../escape.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), escaping_rs)?;
+    rustdocmd(root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("../escape.md"));
+    assert!(!root.join("mdbook").join("escape.md").exists());
+    Ok(())
+}
+
+#[test]
+fn merges_fragments_with_the_same_target() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let files = [
+        (
+            "a.rs",
+            "/// <architecture.md(3)#2>\n/// second\n/// </architecture.md>\npub fn a() {}\n",
+        ),
+        (
+            "b.rs",
+            "/// <architecture.md#1>\n/// # Architecture\n/// </architecture.md>\npub fn b() {}\n",
+        ),
+        (
+            "c.rs",
+            "/// <architecture.md>\n/// unindexed\n/// </architecture.md>\npub fn c() {}\n",
+        ),
+    ];
+    for (name, content) in files {
+        fs::write(src_dir.join(name), content)?;
+    }
+
+    rustdocmd(root).assert().success();
+
+    let chapter = fs::read_to_string(mdbook_src.join("architecture.md"))?;
+    assert_eq!(chapter, "# Architecture\n\nsecond\n\nunindexed");
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert_eq!(summary.matches("(architecture.md)").count(), 1);
+    Ok(())
+}
+
+#[test]
+fn builds_nested_summary_from_dotted_orders() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let sample_rs = r#"
+/// <config-paths.md(2.1.3)>
+/// paths
+/// </config-paths.md>
+/// <config.md(2)>
+/// config
+/// </config.md>
+/// <intro.md(1)>
+/// intro
+/// </intro.md>
+/// <config-env.md(2.2)>
+/// env
+/// </config-env.md>
+/// <faq.md(3)>
+/// faq
+/// </faq.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "2.1.3 (config-paths.md) hat kein Kapitel 2.1",
+        ));
+
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert_eq!(
+        summary,
+        "# Summary\n\n\
+         <!-- rustdocmd:begin -->\n\
+         * [Intro](intro.md)\n\
+         * [Config](config.md)\n    \
+         * [Config Paths](config-paths.md)\n    \
+         * [Config Env](config-env.md)\n\
+         * [Faq](faq.md)\n\
+         <!-- rustdocmd:end -->\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn reports_duplicate_order_numbers() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    fs::write(
+        src_dir.join("b.rs"),
+        "/// <second.md(1)>\n/// b\n/// </second.md>\npub fn b() {}\n",
+    )?;
+    fs::write(
+        src_dir.join("a.rs"),
+        "/// <first.md(1)>\n/// a\n/// </first.md>\npub fn a() {}\n",
+    )?;
+
+    // default policy: warn and keep both chapters, ordered by source file
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "first.md (./src/a.rs:1-3) und second.md (./src/b.rs:1-3) haben dieselbe Nummer 1",
+        ));
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert!(summary.contains("* [First](first.md)\n* [Second](second.md)\n"));
+
+    // error policy: abort before anything is written
+    fs::remove_dir_all(&mdbook_src)?;
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n\n[summary]\nduplicate_order = \"error\"\n",
+    )?;
+    rustdocmd(root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("./src/a.rs:1-3"))
+        .stderr(predicates::str::contains("./src/b.rs:1-3"));
+    assert!(!mdbook_src.join("first.md").exists());
+    Ok(())
+}
+
+#[test]
+fn resolves_after_and_before_constraints() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let sample_rs = r#"
+/// <faq.md after="intro.md">
+/// faq
+/// </faq.md>
+/// <install.md(2)>
+/// install
+/// </install.md>
+/// <intro.md(1)>
+/// intro
+/// </intro.md>
+/// <changelog.md(3) before="install.md">
+/// changelog
+/// </changelog.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+    rustdocmd(root).assert().success();
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert_eq!(
+        summary,
+        "# Summary\n\n\
+         <!-- rustdocmd:begin -->\n\
+         * [Intro](intro.md)\n\
+         * [Faq](faq.md)\n\
+         * [Changelog](changelog.md)\n\
+         * [Install](install.md)\n\
+         <!-- rustdocmd:end -->\n"
+    );
+
+    // unnumbered chapters take the depth of their anchor
+    let nested_rs = r#"
+/// <config.md(2)>
+/// config
+/// </config.md>
+/// <paths.md(2.1)>
+/// paths
+/// </paths.md>
+/// <env.md(2.2)>
+/// env
+/// </env.md>
+/// <faq.md after="paths.md">
+/// faq
+/// </faq.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), nested_rs)?;
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("eingerückt").not());
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert!(
+        summary.contains(
+            "* [Config](config.md)\n    \
+             * [Paths](paths.md)\n    \
+             * [Faq](faq.md)\n    \
+             * [Env](env.md)\n"
+        ),
+        "{}",
+        summary
+    );
+
+    // ... and a warning if they split a numbered subtree
+    fs::write(
+        src_dir.join("sample.rs"),
+        nested_rs.replace("after=\"paths.md\"", "after=\"config.md\""),
+    )?;
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "faq.md (./src/sample.rs:11-13) steht durch after/before vor paths.md (2.1)",
+        ));
+
+    // dangling reference
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <faq.md after=\"missing.md\">\n/// faq\n/// </faq.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("after=\"missing.md\""));
+
+    // cycle
+    let cycle_rs = r#"
+/// <a.md after="b.md">
+/// a
+/// </a.md>
+/// <b.md after="a.md">
+/// b
+/// </b.md>
+pub fn f() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), cycle_rs)?;
+    rustdocmd(root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Zyklus in after/before zwischen a.md, b.md",
+        ));
+    Ok(())
+}
+
+#[test]
+fn reads_attributes_on_opening_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    let sample_rs = r#"
+/// <intro.md(1) title="Getting Started" audience=internal weight=3>
+/// intro
+/// </intro.md>
+/// <roadmap.md(2) draft colour="blue">
+/// later
+/// </roadmap.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Warnung: unbekanntes Attribut 'colour' in <roadmap.md> (./src/sample.rs:5-7)",
+        ));
+
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert!(summary.contains("* [Getting Started](intro.md)\n* [Roadmap]()\n"));
+    assert_eq!(fs::read_to_string(mdbook_src.join("intro.md"))?, "intro");
+    assert!(!mdbook_src.join("roadmap.md").exists());
+    Ok(())
+}
+
+#[test]
+fn takes_summary_titles_from_attribute_heading_or_file_name(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n\n\
+         [summary]\ntitle_transform = \"sentence-case\"\nacronyms = [\"API\"]\n",
+    )?;
+
+    let sample_rs = r#"
+/// <attr.md(1) title="From Attribute">
+/// # Ignored Heading
+/// </attr.md>
+/// <heading.md(2)>
+/// ```sh
+/// # not a heading
+/// ```
+/// ## Installation Guide ##
+/// </heading.md>
+/// <setext.md(3)>
+/// Underlined Title
+/// ================
+/// </setext.md>
+/// <api-ref-notes.md(4)>
+/// no heading here
+/// </api-ref-notes.md>
+pub fn documented() {}
+"#;
+    fs::write(src_dir.join("sample.rs"), sample_rs)?;
+    rustdocmd(root).assert().success();
+
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    assert_eq!(
+        summary,
+        "# Summary\n\n\
+         <!-- rustdocmd:begin -->\n\
+         * [From Attribute](attr.md)\n\
+         * [Installation Guide](heading.md)\n\
+         * [Underlined Title](setext.md)\n\
+         * [API ref notes](api-ref-notes.md)\n\
+         <!-- rustdocmd:end -->\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn keeps_hand_written_summary_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    // summary from an older run plus hand-written prefix, part title and links
+    let hand_written = "# Summary\n\n\
+                        [Preface](preface.md)\n\n\
+                        # Guide\n\n\
+                        * [Intro](./intro.md)\n\
+                        * [Old](old.md)\n\n\
+                        ---\n\n\
+                        [Crates.io](https://crates.io)\n";
+    fs::write(mdbook_src.join("SUMMARY.md"), hand_written)?;
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+
+    let expected = "# Summary\n\n\
+                    [Preface](preface.md)\n\n\
+                    # Guide\n\n\
+                    <!-- rustdocmd:begin -->\n\
+                    * [Intro](intro.md)\n\
+                    <!-- rustdocmd:end -->\n\
+                    * [Old](old.md)\n\n\
+                    ---\n\n\
+                    [Crates.io](https://crates.io)\n";
+    assert_eq!(fs::read_to_string(mdbook_src.join("SUMMARY.md"))?, expected);
+
+    // later runs only touch the managed region
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <setup.md(1)>\n/// # Setup\n/// </setup.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+    assert_eq!(
+        fs::read_to_string(mdbook_src.join("SUMMARY.md"))?,
+        expected.replace("* [Intro](intro.md)", "* [Setup](setup.md)")
+    );
+    Ok(())
+}
+
+#[test]
+fn only_deletes_files_listed_in_the_manifest() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+
+    // hand-written chapter and asset next to the generated ones
+    fs::write(mdbook_src.join("notes.md"), "# Notes\n")?;
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\n\
+         /// <guide/setup.md(2)>\n/// # Setup\n/// </guide/setup.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+    // the manifest lives next to book.toml, not in the book sources
+    assert!(!mdbook_src.join(".rustdocmd-manifest").exists());
+    assert_eq!(
+        fs::read_to_string(root.join("mdbook").join(".rustdocmd-manifest"))?,
+        "# Von rustdocmd erzeugte Dateien (nur diese werden gelöscht)\n\
+         guide/setup.md\n\
+         intro.md\n"
+    );
+
+    // removing a block deletes its file (and the empty folder), nothing else
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    let output = rustdocmd(root).output()?;
+    assert!(output.status.success());
+    assert!(!mdbook_src.join("guide").exists());
+    assert!(mdbook_src.join("intro.md").exists());
+    assert_eq!(
+        fs::read_to_string(mdbook_src.join("notes.md"))?,
+        "# Notes\n"
+    );
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("notes.md wurde nicht von rustdocmd erzeugt und bleibt erhalten"));
+    assert_eq!(
+        fs::read_to_string(root.join("mdbook").join(".rustdocmd-manifest"))?,
+        "# Von rustdocmd erzeugte Dateien (nur diese werden gelöscht)\nintro.md\n"
+    );
+
+    // a manifest left in the target folder by older versions is taken over and removed
+    fs::remove_file(root.join("mdbook").join(".rustdocmd-manifest"))?;
+    fs::write(
+        mdbook_src.join(".rustdocmd-manifest"),
+        "# Von rustdocmd erzeugte Dateien (nur diese werden gelöscht)\nintro.md\n",
+    )?;
+    fs::write(src_dir.join("sample.rs"), "pub fn f() {}\n")?;
+    rustdocmd(root).assert().success();
+    assert!(!mdbook_src.join("intro.md").exists());
+    assert!(!mdbook_src.join(".rustdocmd-manifest").exists());
+    assert!(root.join("mdbook").join(".rustdocmd-manifest").exists());
+    Ok(())
+}
+
+#[test]
+fn injects_readme_blocks_into_named_regions() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("src").join("lib.rs"),
+        r#"
+//! <readme name="usage">
+//! Run `tool`.
+//! </readme>
+
+/// <readme name="about">
+/// About the tool.
+/// </readme>
+/// <readme name="usage">
+/// Then read the output.
+/// </readme>
+pub fn f() {}
+"#,
+    )?;
+    let readme = "[![CI](badge.svg)](ci)\n\n\
+                  ## About\n\
+                  <!-- rustdocmd:begin about -->\n\
+                  old text\n\
+                  <!-- rustdocmd:end about -->\n\n\
+                  ## Usage\n\
+                  <!-- rustdocmd:begin usage -->\n\
+                  <!-- rustdocmd:end usage -->\n\n\
+                  ## License\n\
+                  MIT  \n";
+    fs::write(root.join("README.md"), readme)?;
+
+    rustdocmd(root)
+        .arg("--generate-readme")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("unbekanntes Attribut").not());
+    // README blocks are no chapters
+    assert!(!root.join("mdbook").join("src").join("readme").exists());
+    let summary = fs::read_to_string(root.join("mdbook").join("src").join("SUMMARY.md"))?;
+    assert!(!summary.contains("readme"), "{}", summary);
+    assert_eq!(
+        fs::read_to_string(root.join("README.md"))?,
+        "[![CI](badge.svg)](ci)\n\n\
+         ## About\n\
+         <!-- rustdocmd:begin about -->\n\
+         About the tool.\n\
+         <!-- rustdocmd:end about -->\n\n\
+         ## Usage\n\
+         <!-- rustdocmd:begin usage -->\n\
+         Run `tool`.\n\n\
+         Then read the output.\n\
+         <!-- rustdocmd:end usage -->\n\n\
+         ## License\n\
+         MIT  \n"
+    );
+
+    // CRLF files keep their line endings
+    fs::write(
+        root.join("README.md"),
+        "## Usage\r\n<!-- rustdocmd:begin usage -->\r\nold\r\n<!-- rustdocmd:end usage -->\r\n",
+    )?;
+    rustdocmd(root).arg("--generate-readme").assert().success();
+    assert_eq!(
+        fs::read_to_string(root.join("README.md"))?,
+        "## Usage\r\n<!-- rustdocmd:begin usage -->\r\n\
+         Run `tool`.\r\n\r\n\
+         Then read the output.\r\n\
+         <!-- rustdocmd:end usage -->\r\n"
+    );
+
+    // without regions the README stays untouched
+    fs::write(root.join("README.md"), "# Hand-written\n")?;
+    rustdocmd(root)
+        .arg("--generate-readme")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("wird nicht verändert"));
+    assert_eq!(
+        fs::read_to_string(root.join("README.md"))?,
+        "# Hand-written\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn injects_blocks_into_configured_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n\n\
+         [inject]\nfiles = [\"CONTRIBUTING.md\", \"docs/ops.md\"]\n",
+    )?;
+    fs::write(
+        root.join("src").join("lib.rs"),
+        r#"
+/// <inject file="CONTRIBUTING.md" region="build">
+/// cargo build
+/// </inject>
+/// <inject file="docs/ops.md">
+/// Restart with `systemctl restart tool`.
+/// </inject>
+/// <inject file="SECURITY.md" region="report">
+/// Not configured.
+/// </inject>
+pub fn f() {}
+"#,
+    )?;
+    fs::create_dir_all(root.join("docs"))?;
+    fs::write(
+        root.join("CONTRIBUTING.md"),
+        "# Contributing\n\n<!-- rustdocmd:begin build -->\n<!-- rustdocmd:end build -->\n\nThanks!\n",
+    )?;
+    fs::write(
+        root.join("docs").join("ops.md"),
+        "# Ops\n<!-- rustdocmd:begin -->\nold\n<!-- rustdocmd:end -->\n",
+    )?;
+    fs::write(root.join("SECURITY.md"), "# Security\n")?;
+
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "SECURITY.md ist nicht in [inject] files eingetragen",
+        ))
+        .stderr(predicates::str::contains("unbekanntes Attribut").not());
+    // inject blocks are no chapters
+    assert!(!root.join("mdbook").join("src").join("inject").exists());
+    assert_eq!(
+        fs::read_to_string(root.join("CONTRIBUTING.md"))?,
+        "# Contributing\n\n<!-- rustdocmd:begin build -->\ncargo build\n<!-- rustdocmd:end build -->\n\nThanks!\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("docs").join("ops.md"))?,
+        "# Ops\n<!-- rustdocmd:begin -->\nRestart with `systemctl restart tool`.\n<!-- rustdocmd:end -->\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("SECURITY.md"))?,
+        "# Security\n"
+    );
+    // README.md is only touched with --generate-readme
+    assert!(!root.join("README.md").exists());
+    Ok(())
+}
+
+#[test]
+fn assembles_readme_in_order_with_sections_and_toc() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n\n\
+         [readme]\nseparator = \"\\n\\n---\\n\\n\"\ntoc = true\n",
+    )?;
+    fs::write(
+        root.join("src").join("a.rs"),
+        "/// <readme section=\"Usage\">\n/// Run it.\n/// </readme>\n\
+         /// <readme(1)>\n/// # Tool\n/// </readme>\npub fn a() {}\n",
+    )?;
+    fs::write(
+        root.join("src").join("b.rs"),
+        "/// <readme(2) section=\"Getting Started\">\n/// Install it.\n/// </readme>\npub fn b() {}\n",
+    )?;
+
+    rustdocmd(root).arg("--generate-readme").assert().success();
+    let expected = "<!-- rustdocmd:begin toc -->\n\
+                    - [Getting Started](#getting-started)\n\
+                    - [Usage](#usage)\n\
+                    <!-- rustdocmd:end toc -->\n\n\
+                    <!-- rustdocmd:begin -->\n\
+                    # Tool\n\n---\n\n\
+                    ## Getting Started\n\nInstall it.\n\n---\n\n\
+                    ## Usage\n\nRun it.\n\
+                    <!-- rustdocmd:end -->\n";
+    assert_eq!(fs::read_to_string(root.join("README.md"))?, expected);
+
+    // a second run reproduces the same file
+    rustdocmd(root).arg("--generate-readme").assert().success();
+    assert_eq!(fs::read_to_string(root.join("README.md"))?, expected);
+    Ok(())
+}
+
+#[test]
+fn readme_order_numbers_do_not_clash_with_chapters() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\ntarget = \"./mdbook/src\"\n\n\
+         [summary]\nduplicate_order = \"error\"\n",
+    )?;
+    fs::write(
+        root.join("src").join("lib.rs"),
+        "/// <guide.md(2)>\n/// # Guide\n/// </guide.md>\n\
+         /// <readme(2)>\n/// Second README part.\n/// </readme>\npub fn f() {}\n",
+    )?;
+
+    rustdocmd(root)
+        .arg("--generate-readme")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("dieselbe Nummer").not());
+    let summary = fs::read_to_string(root.join("mdbook").join("src").join("SUMMARY.md"))?;
+    assert!(summary.contains("* [Guide](guide.md)\n"), "{}", summary);
+    assert!(!summary.contains("readme"), "{}", summary);
+    assert!(!root.join("mdbook").join("src").join("readme").exists());
+    assert!(fs::read_to_string(root.join("README.md"))?.contains("Second README part."));
+    Ok(())
+}
+
+#[test]
+fn check_fails_on_stale_output_without_writing() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\n\
+         /// <old.md(2)>\n/// # Old\n/// </old.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+    rustdocmd(root)
+        .arg("--check")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("aktuell"));
+
+    // edited block and removed chapter are reported, nothing is touched
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// Edited.\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+    rustdocmd(root)
+        .arg("--check")
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("veraltet: ./mdbook/src/intro.md"))
+        .stdout(predicates::str::contains(
+            "veraltet: ./mdbook/src/SUMMARY.md",
+        ))
+        .stdout(predicates::str::contains(
+            "würde gelöscht: ./mdbook/src/old.md",
+        ));
+    assert_eq!(fs::read_to_string(mdbook_src.join("intro.md"))?, "# Intro");
+    assert!(mdbook_src.join("old.md").exists());
+    assert_eq!(fs::read_to_string(mdbook_src.join("SUMMARY.md"))?, summary);
+    Ok(())
+}
+
+#[test]
+fn dry_run_shows_unified_diffs_and_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// Old line\n/// </intro.md>\n\
+         /// <old.md(2)>\n/// # Old\n/// </old.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// New line\n/// </intro.md>\n\
+         /// <new.md(2)>\n/// # New\n/// </new.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root)
+        .arg("--dry-run")
+        .env("NO_COLOR", "1")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "@@ -1,2 +1,2 @@\n # Intro\n-Old line\n\\ No newline at end of file\n+New line\n",
+        ))
+        .stdout(predicates::str::contains(
+            "--- /dev/null\n+++ ./mdbook/src/new.md\n@@ -0,0 +1 @@\n+# New\n",
+        ))
+        .stdout(predicates::str::contains(
+            "--- ./mdbook/src/old.md\n+++ /dev/null\n@@ -1 +0,0 @@\n-# Old\n",
+        ))
+        .stdout(predicates::str::contains(
+            "-* [Old](old.md)\n+* [New](new.md)\n",
+        ))
+        .stdout(predicates::str::contains(
+            "[dry-run] 1 neu, 4 geändert, 0 unverändert, 1 gelöscht",
+        ));
+    Ok(())
+}
+
+#[test]
+fn leaves_unchanged_files_untouched() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\n\
+         /// <setup.md(2)>\n/// # Setup\n/// </setup.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("5 Datei(en) aktualisiert"));
+
+    // pin the mtimes to a known value; an unchanged run must keep them
+    let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+    let files = ["intro.md", "setup.md", "SUMMARY.md"].map(|f| mdbook_src.join(f));
+    for file in &files {
+        fs::File::options()
+            .write(true)
+            .open(file)?
+            .set_modified(old)?;
+    }
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "0 Datei(en) aktualisiert (0 neu, 0 geändert, 5 unverändert, 0 gelöscht)",
+        ));
+    for file in &files {
+        assert_eq!(fs::metadata(file)?.modified()?, old);
+    }
+
+    // only the edited chapter is rewritten
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// More.\n/// </intro.md>\n\
+         /// <setup.md(2)>\n/// # Setup\n/// </setup.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1 Datei(en) aktualisiert"));
+    assert_ne!(fs::metadata(&files[0])?.modified()?, old);
+    assert_eq!(fs::metadata(&files[1])?.modified()?, old);
+    assert_eq!(fs::metadata(&files[2])?.modified()?, old);
+    Ok(())
+}
+
+#[test]
+fn failed_run_leaves_previous_output_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let src_dir = root.join("src");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().success();
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+
+    // `guide` is a file, so the second chapter cannot be written
+    fs::write(mdbook_src.join("guide"), "not a folder")?;
+    fs::write(
+        src_dir.join("sample.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// Changed.\n/// </intro.md>\n\
+         /// <guide/setup.md(2)>\n/// # Setup\n/// </guide/setup.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).assert().failure();
+    assert_eq!(fs::read_to_string(mdbook_src.join("intro.md"))?, "# Intro");
+    assert_eq!(fs::read_to_string(mdbook_src.join("SUMMARY.md"))?, summary);
+    let leftovers: Vec<_> = fs::read_dir(&mdbook_src)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.contains(".rustdocmd-"))
+        .filter(|name| name != ".rustdocmd-manifest")
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+    Ok(())
+}
+
+#[test]
+fn unparsable_source_aborts_without_deleting_chapters() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let lib = root.join("src").join("lib.rs");
+    let mdbook_src = root.join("mdbook").join("src");
+    fs::write(&lib, "/// <a.md(1)>\n/// # A\n/// </a.md>\npub fn f() {}\n")?;
+    rustdocmd(root).assert().success();
+    let summary = fs::read_to_string(mdbook_src.join("SUMMARY.md"))?;
+
+    // unterminated string literal
+    fs::write(
+        &lib,
+        "/// <a.md(1)>\n/// # A\n/// </a.md>\npub fn f() {}\nconst S: &str = \"open;\n",
+    )?;
+    rustdocmd(root)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("./src/lib.rs:5"));
+    assert_eq!(fs::read_to_string(mdbook_src.join("a.md"))?, "# A");
+    assert_eq!(fs::read_to_string(mdbook_src.join("SUMMARY.md"))?, summary);
+    Ok(())
+}
+
+#[test]
+fn runs_as_mdbook_preprocessor() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    let book_dir = root.join("mdbook");
+    fs::write(
+        root.join("src").join("lib.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// Hello from source.\n/// </intro.md>\n\
+         /// <guide.md(2)>\n/// # Guide\n/// </guide.md>\n\
+         /// <guide/deep.md(2.1)>\n/// # Deep dive\n/// Nested text.\n/// </guide/deep.md>\n\
+         pub fn f() {}\n",
+    )?;
+    let bin = assert_cmd::cargo::cargo_bin!("rustdocmd");
+    fs::write(
+        book_dir.join("book.toml"),
+        format!(
+            "[book]\ntitle = \"Test\"\n\n[preprocessor.rustdocmd]\ncommand = \"'{}' preprocess\"\n",
+            bin.display()
+        ),
+    )?;
+    // only the intro is listed; its file does not even exist yet
+    fs::write(
+        book_dir.join("src").join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](intro.md)\n",
+    )?;
+
+    rustdocmd(&book_dir)
+        .args(["preprocess", "supports", "html"])
+        .assert()
+        .success();
+    mdbook::MDBook::load(&book_dir)?.build()?;
+    let html = |page: &str| fs::read_to_string(book_dir.join("book").join(page));
+    assert!(html("intro.html")?.contains("Hello from source."));
+    assert!(html("guide.html")?.contains("Guide"));
+    assert!(html("guide/deep.html")?.contains("Nested text."));
+    // no chapter files are generated next to SUMMARY.md
+    assert!(!book_dir.join("src").join("guide.md").exists());
+    Ok(())
+}
+
+#[test]
+fn takes_book_layout_from_book_toml() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\nbook = \"./docs/book.toml\"\n",
+    )?;
+    fs::create_dir_all(root.join("docs"))?;
+    fs::write(
+        root.join("docs").join("book.toml"),
+        "[book]\ntitle = \"My Book\"\nsrc = \"pages\"\n",
+    )?;
+    fs::write(
+        root.join("src").join("lib.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+
+    rustdocmd(root).assert().success();
+    let pages = root.join("docs").join("pages");
+    assert_eq!(fs::read_to_string(pages.join("intro.md"))?, "# Intro");
+    assert_eq!(
+        fs::read_to_string(pages.join("SUMMARY.md"))?,
+        "# My Book\n\n<!-- rustdocmd:begin -->\n* [Intro](intro.md)\n<!-- rustdocmd:end -->\n"
+    );
+    // no mirrored copies next to book.toml or in the old default location
+    assert!(!root.join("docs").join("SUMMARY.md").exists());
+    assert!(!root.join("mdbook").join("src").join("SUMMARY.md").exists());
+    // the result is a book mdBook can build
+    mdbook::MDBook::load(root.join("docs"))?.build()?;
+    assert!(root.join("docs").join("book").join("intro.html").exists());
+    Ok(())
+}
+
+/// Project with `mdbook/book.toml`, configured via `paths.book`
+fn book_project() -> std::io::Result<TempDir> {
+    let dir = project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("rustdocmd.toml"),
+        "[paths]\nsource = \"./src\"\nbook = \"./mdbook/book.toml\"\n",
+    )?;
+    fs::write(
+        root.join("mdbook").join("book.toml"),
+        "[book]\ntitle = \"Test\"\nsrc = \"src\"\n",
+    )?;
+    Ok(dir)
+}
+
+#[test]
+fn build_generates_chapters_and_builds_the_book() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = book_project()?;
+    let root = dir.path();
+    fs::write(
+        root.join("src").join("lib.rs"),
+        "/// <intro.md(1)>\n/// # Intro\n/// Built text.\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    rustdocmd(root).arg("build").assert().success();
+    let html = fs::read_to_string(root.join("mdbook").join("book").join("intro.html"))?;
+    assert!(html.contains("Built text."));
+    Ok(())
+}
+
+/// Plain HTTP GET against the local `serve` subcommand
+fn http_get(port: u16, path: &str) -> std::io::Result<String> {
+    use std::io::{Read, Write};
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))?;
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+#[test]
+fn serve_rebuilds_after_source_changes() -> Result<(), Box<dyn std::error::Error>> {
+    use std::time::{Duration, Instant};
+    let dir = book_project()?;
+    let root = dir.path();
+    let lib = root.join("src").join("lib.rs");
+    fs::write(
+        &lib,
+        "/// <intro.md(1)>\n/// # Intro\n/// First.\n/// </intro.md>\n\
+         /// <über.md(2)>\n/// # Über\n/// Umlaut.\n/// </über.md>\npub fn f() {}\n",
+    )?;
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let mut child = rustdocmd(root)
+        .args([
+            "serve",
+            "--hostname",
+            "127.0.0.1",
+            "--port",
+            &port.to_string(),
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    // waits until `path` contains `text`, at most 20 seconds
+    let wait_for = |path: &str, text: &str| {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(20) {
+            if http_get(port, path).is_ok_and(|r| r.contains(text)) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        false
+    };
+    let first = wait_for("/intro.html", "First.");
+    let reload_script =
+        http_get(port, "/intro.html").is_ok_and(|r| r.contains("/__rustdocmd/version"));
+    let encoded = http_get(port, "/%C3%BCber.html").is_ok_and(|r| r.contains("Umlaut."));
+    fs::write(
+        &lib,
+        "/// <intro.md(1)>\n/// # Intro\n/// Second.\n/// </intro.md>\npub fn f() {}\n",
+    )?;
+    let second = wait_for("/intro.html", "Second.");
+    let version = http_get(port, "/__rustdocmd/version")?;
+    child.kill()?;
+    child.wait()?;
+
+    assert!(first, "book was not served");
+    assert!(reload_script, "live reload script missing");
+    assert!(encoded, "percent-encoded path was not resolved");
+    assert!(second, "book was not rebuilt after the change");
+    let version: usize = version.rsplit("\r\n\r\n").next().unwrap_or("").parse()?;
+    assert!(version >= 1, "version was not bumped");
+    Ok(())
+}
+
+#[test]
+fn watch_updates_chapters_of_changed_files() -> Result<(), Box<dyn std::error::Error>> {
+    use std::time::{Duration, Instant};
+    let dir = project()?;
+    let root = dir.path();
+    let a = root.join("src").join("a.rs");
+    let b = root.join("src").join("b.rs");
+    let intro = root.join("mdbook").join("src").join("intro.md");
+    let setup = root.join("mdbook").join("src").join("setup.md");
+    fs::write(
+        &a,
+        "/// <intro.md(1)>\n/// # Intro\n/// First.\n/// </intro.md>\npub fn a() {}\n",
+    )?;
+    fs::write(
+        &b,
+        "/// <setup.md(2)>\n/// # Setup\n/// Steps.\n/// </setup.md>\npub fn b() {}\n",
+    )?;
+    let mut child = rustdocmd(root)
+        .arg("watch")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    // waits until `check` holds, at most 20 seconds
+    let wait_for = |check: &dyn Fn() -> bool| {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(20) {
+            if check() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        false
+    };
+    let contains = |path: &Path, text: &str| {
+        fs::read_to_string(path).is_ok_and(|content| content.contains(text))
+    };
+    let first = wait_for(&|| contains(&intro, "First.") && contains(&setup, "Steps."));
+    // give the watcher time to start before changing sources
+    std::thread::sleep(Duration::from_millis(500));
+    let setup_mtime = fs::metadata(&setup).and_then(|m| m.modified()).ok();
+
+    fs::write(
+        &a,
+        "/// <intro.md(1)>\n/// # Intro\n/// Second.\n/// </intro.md>\npub fn a() {}\n",
+    )?;
+    let edited = wait_for(&|| contains(&intro, "Second."));
+    let setup_untouched = fs::metadata(&setup).and_then(|m| m.modified()).ok() == setup_mtime;
+
+    // a half-typed edit keeps the chapters of the file until it parses again
+    fs::write(
+        &a,
+        "/// <intro.md(1)>\n/// # Intro\n/// Second.\n/// </intro.md>\npub fn a() { \"open\n",
+    )?;
+    std::thread::sleep(Duration::from_millis(500));
+    fs::write(
+        &b,
+        "/// <setup.md(2)>\n/// # Setup\n/// More steps.\n/// </setup.md>\npub fn b() {}\n",
+    )?;
+    let later = wait_for(&|| contains(&setup, "More steps."));
+    let survived = contains(&intro, "Second.");
+
+    // move the intro block from a.rs to b.rs
+    fs::write(&a, "pub fn a() {}\n")?;
+    fs::write(
+        &b,
+        "/// <intro.md(1)>\n/// # Intro\n/// Moved.\n/// </intro.md>\npub fn a2() {}\n\
+         /// <setup.md(2)>\n/// # Setup\n/// Steps.\n/// </setup.md>\npub fn b() {}\n",
+    )?;
+    let moved = wait_for(&|| contains(&intro, "Moved.") && !contains(&intro, "Second."));
+
+    fs::remove_file(&b)?;
+    let removed = wait_for(&|| !intro.exists() && !setup.exists());
+    child.kill()?;
+    child.wait()?;
 
+    assert!(first, "initial run did not write the chapters");
+    assert!(edited, "chapter was not updated after the change");
+    assert!(setup_untouched, "unchanged chapter was rewritten");
+    assert!(later, "chapter was not updated after the invalid save");
+    assert!(survived, "chapter of the unparsable file was removed");
+    assert!(moved, "moved block was not picked up");
+    assert!(removed, "chapters of the removed file were not deleted");
     Ok(())
 }